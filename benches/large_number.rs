@@ -1,5 +1,6 @@
-use rand_key::RandKey;
+use rand_key::{RandKey, CharClass};
 use criterion::{criterion_group, criterion_main, Criterion};
+use std::io::sink;
 
 
 
@@ -11,9 +12,30 @@ fn init_randkey(number: (&str, &str, &str)) -> Result<(), Box<dyn std::error::Er
 }
 
 
+fn init_unicode(cnt: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut r_p = RandKey::new(cnt, "0", cnt)?;
+    r_p.add_class(CharClass::from_chars("CJK", '\u{4E00}'..='\u{9FFF}', cnt)?)?;
+    r_p.join()?;
+    Ok(())
+}
+
+
+fn stream_randkey(number: (&str, &str, &str)) -> Result<(), Box<dyn std::error::Error>> {
+    let r_p = RandKey::new(number.0, number.1, number.2)?;
+    r_p.join_to_writer(sink())?;
+    Ok(())
+}
+
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("RandKey: 1000000 1000000 1000000", |b| b.iter(|| init_randkey(("1000000", "1000000", "1000000"))));
-    c.bench_function("RandKey: 100000000 0 0", |b| b.iter(|| init_randkey(("1000000", "1000000", "1000000"))));
+
+    let mut group = c.benchmark_group("Large");
+    group.sample_size(10);
+    group.bench_function("RandKey: 100000000 0 0", |b| b.iter(|| init_randkey(("100000000", "0", "0"))));
+    group.bench_function("RandKey: 1000000 0 1000000 + 1000000 CJK", |b| b.iter(|| init_unicode("1000000")));
+    group.bench_function("RandKey::join_to_writer: 1000000 1000000 1000000", |b| b.iter(|| stream_randkey(("1000000", "1000000", "1000000"))));
+    group.finish();
 }
 
 
@@ -1,5 +1,5 @@
 use rand_key::RandKey;
-use std::{env::args, error::Error};
+use std::{env::args, error::Error, io::stdout};
 
 
 
@@ -13,7 +13,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     if demands.is_empty() {
         r_p = RandKey::new("10", "2", "3")?;
         r_p.join()?;
-        println!("{}", r_p);
+        println!("{}", r_p.reveal());
     } else {
         let ltr_cnt = &demands[0];
         let sbl_cnt = &demands[1];
@@ -26,9 +26,9 @@ fn main() -> Result<(), Box<dyn Error>> {
             r_p.set_unit(unit)?;
         }
 
-        r_p.join()?;
-
-        println!("{}", r_p);
+        // Huge keys go straight to the standard output
+        r_p.join_to_writer(stdout().lock())?;
+        println!();
     }
 
     Ok(())
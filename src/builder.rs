@@ -0,0 +1,169 @@
+use {
+    std::ops::RangeBounds,
+    crate::{
+        Count, RandKey, RngSource,
+        class::{CharClass, Table},
+        error::GenError,
+        utils::{_CHECK_ITEM, thread_rng},
+    },
+};
+
+
+
+
+/// Builder of `RandKey`, which collects the whole configuration and validates it once in `build`
+///
+/// Every method takes and returns the builder, the first error is kept and returned by `build`,
+/// so the steps may come in any order.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{RandKeyBuilder, CharClass, RngSource, ASCIIExcludeCtrl::*};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let r_p = RandKeyBuilder::new()
+///     .cnt(Alphabetic, 12)
+///     .cnt(Digit, 4)
+///     .class(CharClass::new("Space", &[" "], 1)?)
+///     .exclude(&["l", "I", "0", "O"])
+///     .unit(1000)
+///     .rng(RngSource::Os)
+///     .build()?;
+///
+/// r_p.join()?;
+/// assert_eq!(r_p.len(), 17);
+/// assert!(!r_p.key().contains(|x| "lI0O".contains(x)));
+///
+/// // Nothing to draw 2 symbols from
+/// assert!(RandKeyBuilder::new().data(&["a", "1"]).cnt(Punctuation, 2).build().is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct RandKeyBuilder {
+    r_p:     RandKey,
+    exclude: Vec<String>,
+    err:     Option<GenError>,
+}
+
+
+impl RandKeyBuilder {
+    /// Return a builder of the default classes, all of them require zero characters
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Return a builder without any class
+    #[inline]
+    #[cfg(feature = "serde")]
+    pub(crate) fn empty() -> Self {
+        let mut builder = Self::default();
+        builder.r_p.DATA.clear();
+        builder
+    }
+
+    /// Set the count of the given class, see [`RandKey::set_cnt`]
+    #[inline]
+    pub fn cnt(mut self, kind: impl AsRef<str>, val: impl Count) -> Self {
+        let result = self.r_p.set_cnt(kind, val);
+        self.keep(result)
+    }
+
+    /// Let the count of the given class vary in `range`, see [`RandKey::set_range`]
+    #[inline]
+    pub fn range(mut self, kind: impl AsRef<str>, range: impl RangeBounds<usize>) -> Self {
+        let result = self.r_p.set_range(kind, range);
+        self.keep(result)
+    }
+
+    /// Limit the length of the key to `range`, see [`RandKey::set_len_range`]
+    #[inline]
+    pub fn len_range(mut self, range: impl RangeBounds<usize>) -> Self {
+        let result = self.r_p.set_len_range(range);
+        self.keep(result)
+    }
+
+    /// Add a class, see [`RandKey::add_class`]
+    #[inline]
+    pub fn class(mut self, class: CharClass) -> Self {
+        let result = self.r_p.add_class(class);
+        self.keep(result)
+    }
+
+    /// Replace the data of the `Alphabetic`, `Punctuation` and `Digit` classes, see [`RandKey::replace_data`]
+    ///
+    /// Unlike `replace_data`, missing characters are only reported by `build`.
+    #[inline]
+    pub fn data(mut self, val: &[impl AsRef<str>]) -> Self {
+        let result = _CHECK_ITEM(val).map(|_| self.r_p.regroup(val, |class, items| class.data = Table::new(&items)));
+        self.keep(result)
+    }
+
+    /// Add data to the `Alphabetic`, `Punctuation` and `Digit` classes, see [`RandKey::add_item`]
+    #[inline]
+    pub fn add_item(mut self, val: &[impl AsRef<str>]) -> Self {
+        let result = self.r_p.add_item(val);
+        self.keep(result)
+    }
+
+    /// Keep these characters out of every class
+    ///
+    /// They are removed in `build`, after all the data are added, and it's fine if no class has them.
+    #[inline]
+    pub fn exclude(mut self, val: &[impl AsRef<str>]) -> Self {
+        let result = _CHECK_ITEM(val).map(|_| self.exclude.extend(val.iter().map(|x| x.as_ref().into())));
+        self.keep(result)
+    }
+
+    /// Skip the characters which look alike, or not, see [`RandKey::set_unambiguous`]
+    #[inline]
+    pub fn unambiguous(mut self, val: bool) -> Self {
+        self.r_p.set_unambiguous(val);
+        self
+    }
+
+    /// Set the value of `UNIT`, see [`RandKey::set_unit`]
+    #[inline]
+    pub fn unit(self, val: impl Count) -> Self {
+        let result = self.r_p.set_unit(val);
+        self.keep(result)
+    }
+
+    /// Set the source of randomness, see [`RandKey::set_rng`]
+    #[inline]
+    pub fn rng(mut self, rng: RngSource) -> Self {
+        self.r_p.set_rng(rng);
+        self
+    }
+
+    /// Validate the configuration and return a `RandKey` ready to `join`
+    ///
+    /// It fails with the first error of the steps, or if some class which requires characters has none,
+    /// or if no key fits the ranges.
+    #[inline]
+    pub fn build(self) -> Result<RandKey, GenError> {
+        let RandKeyBuilder { mut r_p, exclude, err } = self;
+
+        if let Some(e) = err {
+            return Err(e);
+        }
+
+        r_p.DATA.iter_mut().for_each(|class| class.data.retain(|x| !exclude.iter().any(|y| y == x)));
+        r_p.check_data()?;
+
+        if r_p.is_ranged() {
+            r_p.composition(&r_p.active_data(), &mut thread_rng())?;
+        }
+
+        Ok(r_p)
+    }
+
+    /// Keep the first error
+    #[inline]
+    fn keep(mut self, result: Result<(), GenError>) -> Self {
+        if let (None, Err(e)) = (&self.err, result) {
+            self.err = Some(e);
+        }
+        self
+    }
+}
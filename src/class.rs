@@ -0,0 +1,211 @@
+use {
+    std::{collections::HashSet, fmt, ops::Range},
+    crate::{
+        error::GenError,
+        Count,
+        utils::{_BELOW, _CHECK_ITEM, Rng},
+    },
+};
+
+
+
+
+/// A named set of characters, and how many of them the key requires
+///
+/// The default `RandKey` carries three of them: `Alphabetic`, `Punctuation` and `Digit`.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{RandKey, CharClass};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let vowel = CharClass::new("Vowel", &["a", "e", "i", "o", "u"], "4")?;
+/// assert_eq!(vowel.name(), "Vowel");
+/// assert_eq!(vowel.data(), ["a", "e", "i", "o", "u"]);
+/// assert_eq!(vowel.len(), 5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CharClass {
+    pub(crate) name:  String,
+    pub(crate) cnt:   usize,
+    pub(crate) range: Option<(usize, Option<usize>)>,
+    pub(crate) data:  Table,
+}
+
+
+/// The characters of a class packed in one buffer, which `join` draws from
+///
+/// Classes of single-byte characters, or of characters which all have the same width,
+/// are indexed directly, the others keep the byte range of every character.
+/// The class keeps it up to date, so nothing is packed again when a key is generated.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub(crate) struct Table {
+    bytes: String,
+    width: Option<usize>,
+    spans: Vec<Range<usize>>,
+    len:   usize,
+}
+
+
+impl CharClass {
+    /// Return a class called `name`, which takes `cnt` characters from `data`
+    #[inline]
+    pub fn new(name: impl Into<String>, data: &[impl AsRef<str>], cnt: impl Count) -> Result<Self, GenError> {
+        _CHECK_ITEM(data)?;
+
+        let mut class = CharClass { name: name.into(), cnt: cnt.to_count()?, range: None, data: Table::default() };
+        class.extend(data);
+
+        Ok(class)
+    }
+
+    /// Return a class called `name`, which takes `cnt` characters from `chars`
+    ///
+    /// It's handy for Unicode blocks, control characters are skipped.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, CharClass};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("0", "0", "2")?;
+    /// r_p.add_class(CharClass::from_chars("Latin-1", '\u{C0}'..='\u{FF}', "2")?)?;
+    /// r_p.add_class(CharClass::from_chars("Cyrillic", 'а'..='я', "4")?)?;
+    /// r_p.add_class(CharClass::from_chars("CJK", '\u{4E00}'..='\u{9FFF}', "2")?)?;
+    /// r_p.add_class(CharClass::from_chars("Emoji", '\u{1F600}'..='\u{1F64F}', "1")?)?;
+    /// r_p.join()?;
+    ///
+    /// assert_eq!(r_p.key().chars().count(), 11);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn from_chars(name: impl Into<String>, chars: impl IntoIterator<Item = char>, cnt: impl Count)
+        -> Result<Self, GenError> {
+        let data: Vec<String> = chars.into_iter().filter(|x| !x.is_control()).map(String::from).collect();
+        Self::new(name, &data, cnt)
+    }
+
+    /// Return the name of the class
+    #[inline]
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Return the characters of the class
+    ///
+    /// They are packed in one buffer, the slices point into it.
+    #[inline]
+    pub fn data(&self) -> Vec<&str> { self.data.iter().collect() }
+
+    /// Return the number of characters of the class
+    #[inline]
+    pub fn len(&self) -> usize { self.data.len() }
+
+    /// Returns true if the class has no character
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.data.is_empty() }
+
+    /// Append the items which are not in the class yet
+    #[inline]
+    pub(crate) fn extend(&mut self, items: &[impl AsRef<str>]) {
+        let mut seen: HashSet<String> = self.data.iter().map(String::from).collect();
+
+        items.iter().for_each(|x| {
+            if seen.insert(x.as_ref().into()) {
+                self.data.push(x.as_ref());
+            }
+        });
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, item: impl AsRef<str>) -> bool { self.data.iter().any(|x| x == item.as_ref()) }
+}
+
+
+impl Table {
+    /// Pack the items as they are
+    #[inline]
+    pub(crate) fn new(items: &[impl AsRef<str>]) -> Self {
+        let mut table = Table::default();
+        table.bytes.reserve(items.iter().map(|x| x.as_ref().len()).sum());
+        items.iter().for_each(|x| table.push(x.as_ref()));
+
+        table
+    }
+
+    /// Append a character, the byte ranges are only kept once the widths differ
+    #[inline]
+    pub(crate) fn push(&mut self, x: &str) {
+        match self.width {
+            _ if self.len == 0 => self.width = Some(x.len()),
+            Some(w) if w != x.len() => {
+                self.spans = (0..self.len).map(|i| i * w..(i + 1) * w).collect();
+                self.width = None;
+            }
+            _ => (),
+        }
+
+        if self.width.is_none() {
+            self.spans.push(self.bytes.len()..self.bytes.len() + x.len());
+        }
+        self.bytes.push_str(x);
+        self.len += 1;
+    }
+
+    /// Keep the characters which meet `pred`, packed again
+    #[inline]
+    pub(crate) fn retain(&mut self, pred: impl Fn(&str) -> bool) {
+        *self = Table::new(&self.iter().filter(|x| pred(x)).collect::<Vec<_>>());
+    }
+
+    /// Remove all the characters
+    #[inline]
+    pub(crate) fn clear(&mut self) { *self = Table::default(); }
+
+    /// Return the number of characters
+    #[inline]
+    pub(crate) fn len(&self) -> usize { self.len }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Return the `i`-th character, `i` must be below the length
+    #[inline]
+    pub(crate) fn get(&self, i: usize) -> &str {
+        match self.width {
+            Some(w) => &self.bytes[i * w..(i + 1) * w],
+            None => &self.bytes[self.spans[i].clone()],
+        }
+    }
+
+    /// Return the characters in order
+    #[inline]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &str> + '_ { (0..self.len).map(move |i| self.get(i)) }
+
+    /// Return the index of a character
+    #[inline]
+    pub(crate) fn position(&self, item: &str) -> Option<usize> { self.iter().position(|x| x == item) }
+
+    /// Return the width in bytes shared by all the characters, `None` if they differ
+    #[inline]
+    pub(crate) fn width(&self) -> Option<usize> { self.width }
+
+    /// Return the widest character in bytes
+    #[inline]
+    pub(crate) fn max_width(&self) -> usize {
+        self.width.unwrap_or_else(|| self.spans.iter().map(|x| x.len()).max().unwrap_or(0))
+    }
+
+    /// Pick a character uniformly
+    #[inline]
+    pub(crate) fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &str { self.get(_BELOW(self.len, rng)) }
+}
+
+
+impl fmt::Debug for Table {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.debug_list().entries(self.iter()).finish() }
+}
@@ -0,0 +1,534 @@
+use {
+    std::cmp::Ordering,
+    crate::{
+        error::GenError,
+        utils::{_TOO_LARGE, BigUint, One, ToPrimitive, Zero},
+    },
+    zeroize::Zeroizing,
+};
+
+
+
+
+/// How strong the keys of a `RandKey` configuration are
+///
+/// * **keyspace** : The number of distinct keys `join` is able to generate.
+///
+/// * **bits** : The Shannon entropy of `join` in bits. With exact counts every key is equally likely,
+///   so it's `log2(keyspace)`. With ranges the composition is picked uniformly first, which makes it a bit lower.
+///
+/// Classes are assumed to share no character, or some keys are counted more than once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entropy {
+    keyspace: BigUint,
+    bits:     f64,
+}
+
+
+impl Entropy {
+    /// Return the number of distinct keys
+    #[inline]
+    pub fn keyspace(&self) -> &BigUint { &self.keyspace }
+
+    /// Return the entropy in bits
+    #[inline]
+    pub fn bits(&self) -> f64 { self.bits }
+
+    /// The entropy of a generator which picks one of `keyspace` keys uniformly
+    #[inline]
+    pub(crate) fn uniform(keyspace: BigUint) -> Self {
+        let bits = _LOG2(&keyspace);
+        Entropy { keyspace, bits }
+    }
+
+    /// The entropy of the keys which have `cnts[i]` characters from a class of `sizes[i]` characters, see `_KEYSPACE`
+    ///
+    /// The bits come from the counts, not from the keyspace.
+    #[inline]
+    pub(crate) fn exact(cnts: &[usize], sizes: &[usize]) -> Result<Self, GenError> {
+        Ok(Entropy { keyspace: _KEYSPACE(cnts, sizes)?, bits: _BITS(cnts, sizes) })
+    }
+
+    /// The entropy of a generator which picks one of `cnt` compositions uniformly,
+    /// then one of the keys of that composition uniformly
+    ///
+    /// `log_sum` is the sum of the logarithms to base 2 of the keyspaces of all the compositions.
+    #[inline]
+    pub(crate) fn composed(keyspace: BigUint, cnt: f64, log_sum: f64) -> Self {
+        let bits = if cnt > 0.0 { cnt.log2() + log_sum / cnt } else { 0.0 };
+        Entropy { keyspace, bits }
+    }
+}
+
+
+/// The number of keys which have `cnts[i]` characters from a class of `sizes[i]` characters, in any order
+///
+/// It's the multinomial coefficient of the counts, times `sizes[i]` to the power of `cnts[i]` of every class.
+#[inline]
+pub(crate) fn _KEYSPACE(cnts: &[usize], sizes: &[usize]) -> Result<BigUint, GenError> {
+    Ok(_ARRANGEMENTS(cnts)? * _CHARS(cnts, sizes)?)
+}
+
+
+/// The number of ways to arrange the classes, the multinomial coefficient of the counts
+///
+/// Every prime is raised to its exponent in `len! / (cnts[0]! cnts[1]! ..)` by the formula of Legendre,
+/// then the powers are multiplied in a product tree. So there's no division, and no number grows one factor at a time.
+#[inline]
+pub(crate) fn _ARRANGEMENTS(cnts: &[usize]) -> Result<BigUint, GenError> {
+    let len = cnts.iter().try_fold(0usize, |sum, x| sum.checked_add(*x)).filter(|x| x.to_u32().is_some()).ok_or_else(|| _TOO_LARGE(cnts))?;
+
+    // The largest count cancels out the most, the arrangements of one class are just one
+    let max = cnts.iter().copied().max().unwrap_or(0);
+    if max == len {
+        return Ok(BigUint::one());
+    }
+
+    let exp = |n: usize, p: usize| {
+        let (mut n, mut e) = (n, 0);
+        while n >= p {
+            n /= p;
+            e += n;
+        }
+        e
+    };
+
+    let powers: Vec<BigUint> =
+        _PRIMES(len).ok_or_else(|| _TOO_LARGE(cnts))?
+                    .into_iter()
+                    .map(|p| (p, exp(len, p) - cnts.iter().map(|c| exp(*c, p)).sum::<usize>()))
+                    .filter(|(_, e)| *e > 0)
+                    .map(|(p, e)| BigUint::from(p).pow(e as u32))
+                    .collect();
+
+    Ok(_PRODUCT(powers))
+}
+
+
+/// The number of ways to pick the characters, `sizes[i]` to the power of `cnts[i]` of every class
+#[inline]
+pub(crate) fn _CHARS(cnts: &[usize], sizes: &[usize]) -> Result<BigUint, GenError> {
+    let powers = cnts.iter().zip(sizes).map(|(cnt, size)| {
+        let exp = cnt.to_u32().ok_or_else(|| GenError::TooLarge(cnt.to_string()))?;
+        Ok(BigUint::from(*size).pow(exp))
+    });
+
+    Ok(_PRODUCT(powers.collect::<Result<_, GenError>>()?))
+}
+
+
+/// Multiply the numbers in pairs, then the products in pairs, and so on
+///
+/// The factors of every multiplication have about the same size, which the fast multiplications need.
+#[inline]
+fn _PRODUCT(mut xs: Vec<BigUint>) -> BigUint {
+    while xs.len() > 1 {
+        let mut pairs = xs.into_iter();
+        let mut next = Vec::with_capacity(pairs.len() / 2 + 1);
+
+        while let Some(x) = pairs.next() {
+            next.push(match pairs.next() {
+                Some(y) => x * y,
+                None => x,
+            });
+        }
+
+        xs = next;
+    }
+
+    xs.pop().unwrap_or_else(BigUint::one)
+}
+
+
+/// The primes up to `n`, by the sieve of Eratosthenes, `None` if the sieve doesn't fit in the memory
+#[inline]
+fn _PRIMES(n: usize) -> Option<Vec<usize>> {
+    let mut composite = Vec::new();
+    composite.try_reserve_exact(n + 1).ok()?;
+    composite.resize(n + 1, false);
+    let mut primes = vec![];
+
+    for i in 2..=n {
+        if !composite[i] {
+            primes.push(i);
+            (i.saturating_mul(i)..=n).step_by(i).for_each(|j| composite[j] = true);
+        }
+    }
+
+    Some(primes)
+}
+
+
+/// Logarithm to base 2 of `_KEYSPACE`, from the logarithms of the factorials, without the big number
+#[inline]
+pub(crate) fn _BITS(cnts: &[usize], sizes: &[usize]) -> f64 {
+    let len: usize = cnts.iter().sum();
+
+    cnts.iter().zip(sizes).fold(_LOG2_FACT(len), |bits, (cnt, size)| match cnt {
+        0 => bits,
+        _ => bits - _LOG2_FACT(*cnt) + *cnt as f64 * (*size as f64).log2(),
+    })
+}
+
+
+/// Logarithm to base 2 of `n!`
+///
+/// Small ones are summed up, the others come from the series of Stirling, which is exact to the precision of a `f64` there.
+#[inline]
+pub(crate) fn _LOG2_FACT(n: usize) -> f64 {
+    if n < 64 {
+        return (2..=n).map(|x| (x as f64).log2()).sum();
+    }
+
+    let x = n as f64;
+    let ln = x * x.ln() - x + 0.5 * (2.0 * std::f64::consts::PI * x).ln() + 1.0 / (12.0 * x) - 1.0 / (360.0 * x.powi(3));
+
+    ln / std::f64::consts::LN_2
+}
+
+
+/// The class of every position of a key, and the index of its character in the class
+pub(crate) type Positions = (Zeroizing<Vec<usize>>, Zeroizing<Vec<usize>>);
+
+
+/// Return the class of every position and the index of its character in the class, of the `idx`-th key of `_KEYSPACE`
+///
+/// The keys are ordered by the arrangement of the classes first, then by the characters,
+/// both with the first position as the most significant one. `idx` must be below the keyspace.
+#[inline]
+#[rustfmt::skip]
+pub(crate) fn _UNRANK(cnts: &[usize], sizes: &[usize], idx: &BigUint) -> Result<Positions, GenError> {
+
+    let mut arrangements = _ARRANGEMENTS(cnts)?;
+    let chars = _CHARS(cnts, sizes)?;
+
+    let mut arrangement = idx / &chars;
+    let mut chr = idx % &chars;
+
+    let mut left = cnts.to_vec();
+    let mut total: usize = cnts.iter().sum();
+    let mut classes = Zeroizing::new(Vec::with_capacity(total));
+
+    // Skip the arrangements which start with an earlier class, `sub` of them start with class `i`
+    while total > 0 {
+        for (i, cnt) in left.iter_mut().enumerate().filter(|x| *x.1 > 0) {
+            let sub = &arrangements * *cnt / total;
+
+            if arrangement < sub {
+                classes.push(i);
+                arrangements = sub;
+                *cnt -= 1;
+                break;
+            }
+
+            arrangement -= sub;
+        }
+
+        total -= 1;
+    }
+
+    let mut picks = Zeroizing::new(vec![0; classes.len()]);
+
+    for (pick, class) in picks.iter_mut().zip(classes.iter()).rev() {
+        let size = BigUint::from(sizes[*class]);
+        *pick = (&chr % &size).to_usize().unwrap_or_default();
+        chr /= size;
+    }
+
+    Ok((classes, picks))
+
+}
+
+
+/// The index of a key in the order of `_UNRANK`, from the class of every position and the index of its character
+///
+/// The classes must have exactly `cnts[i]` positions of class `i`.
+#[inline]
+pub(crate) fn _RANK(cnts: &[usize], sizes: &[usize], classes: &[usize], picks: &[usize]) -> Result<BigUint, GenError> {
+    let mut arrangements = _ARRANGEMENTS(cnts)?;
+    let chars = _CHARS(cnts, sizes)?;
+
+    let mut arrangement = BigUint::zero();
+    let mut chr = BigUint::zero();
+
+    let mut left = cnts.to_vec();
+    let mut total: usize = cnts.iter().sum();
+
+    for (class, pick) in classes.iter().zip(picks) {
+        for i in (0..*class).filter(|i| left[*i] > 0) {
+            arrangement += &arrangements * left[i] / total;
+        }
+
+        arrangements = arrangements * left[*class] / total;
+        left[*class] -= 1;
+        total -= 1;
+
+        chr = chr * sizes[*class] + *pick;
+    }
+
+    Ok(arrangement * chars + chr)
+}
+
+
+/// Pick the smallest counts of classes, whose keyspace has at least `bits` bits
+///
+/// Without `ratio`, one character is added at a time to the class which grows the keyspace most,
+/// that gives the largest keyspace of each length. With `ratio`, the counts keep the ratio.
+#[inline]
+pub(crate) fn _FIT(sizes: &[usize], bits: f64, ratio: Option<&[usize]>) -> Result<Vec<usize>, GenError> {
+    let weights: Vec<usize> = match ratio {
+        Some(ratio) if ratio.len() == sizes.len() => ratio.to_vec(),
+        Some(_) => return Err(GenError::InvalidRatio),
+        None => sizes.iter().map(|x| (*x > 0) as usize).collect(),
+    };
+
+    if weights.iter().zip(sizes).any(|(w, size)| *w > 0 && *size == 0) || weights.iter().all(|w| *w == 0) {
+        return Err(GenError::InvalidRatio);
+    }
+
+    // Nothing grows the keyspace if there's only one character to pick
+    let reachable = weights.iter().zip(sizes).filter(|(w, _)| **w > 0).map(|(_, size)| size).sum::<usize>() > 1;
+    if !bits.is_finite() || (bits > 0.0 && !reachable) {
+        return Err(GenError::InvalidEntropy);
+    }
+
+    let mut cnts = vec![0usize; sizes.len()];
+    let mut len = 0usize;
+    let mut estimate = 0f64;
+
+    // How many bits adding a character of class `i` brings
+    let gain = |cnts: &[usize], len: usize, i: usize| {
+        (sizes[i] as f64).log2() + ((len + 1) as f64).log2() - ((cnts[i] + 1) as f64).log2()
+    };
+
+    loop {
+        if estimate >= bits && _BITS(&cnts, sizes) >= bits {
+            return Ok(cnts);
+        }
+
+        let picks: Vec<usize> = match ratio {
+            Some(_) => weights.iter().enumerate().flat_map(|(i, w)| vec![i; *w]).collect(),
+            None => {
+                let best = (0..sizes.len())
+                    .filter(|i| weights[*i] > 0)
+                    .max_by(|a, b| gain(&cnts, len, *a).partial_cmp(&gain(&cnts, len, *b)).unwrap_or(Ordering::Equal))
+                    .ok_or(GenError::InvalidRatio)?;
+                vec![best]
+            }
+        };
+
+        picks.into_iter().for_each(|i| {
+            estimate += gain(&cnts, len, i);
+            cnts[i] += 1;
+            len += 1;
+        });
+    }
+}
+
+
+/// The longest key whose keyspace is counted with ranges, beyond it `entropy`, `rank` and `unrank` fail
+///
+/// The counting takes time in proportion to the number of classes times the square of the length.
+pub const MAX_RANGED_LEN: usize = 512;
+
+
+/// The keyspace of a ranged configuration, counted by the length of the key instead of by every composition
+///
+/// `tails[j][q]` is the number of ways to finish a key which has `q` characters from the classes before `j`,
+/// with the classes from `j` on, the arrangements of all the positions included.
+pub(crate) struct Ranged<'a> {
+    bounds: &'a [(usize, usize)],
+    sizes:  &'a [usize],
+    total:  (usize, usize),
+    tails:  Vec<Vec<BigUint>>,
+}
+
+
+impl<'a> Ranged<'a> {
+    /// Count the keys whose counts fall in `bounds` and whose length falls in `total`
+    ///
+    /// It's `LongRange` if the length may exceed [`MAX_RANGED_LEN`].
+    #[inline]
+    pub(crate) fn new(bounds: &'a [(usize, usize)], total: (usize, usize), sizes: &'a [usize]) -> Result<Self, GenError> {
+        let hi = total.1.min(bounds.iter().fold(0usize, |sum, x| sum.saturating_add(x.1)));
+        if hi > MAX_RANGED_LEN {
+            return Err(GenError::LongRange { len: hi, max: MAX_RANGED_LEN });
+        }
+        let total = (total.0, hi);
+
+        // keys[r] is the number of keys of length `r` from the classes so far, from the last one backwards
+        let mut keys = vec![BigUint::zero(); hi + 1];
+        keys[0] = BigUint::one();
+        let mut tails = vec![_TAIL(&keys, total)];
+
+        for ((min, max), size) in bounds.iter().zip(sizes).rev() {
+            keys = (0..=hi).map(|r| {
+                let mut sum = BigUint::zero();
+                let mut ways = BigUint::one();
+
+                // `ways` is `C(r, c)` times `size` to the power of `c`
+                for c in 0..=r.min(*max) {
+                    if c >= *min && !keys[r - c].is_zero() {
+                        sum += &ways * &keys[r - c];
+                    }
+                    ways = ways * (r - c) * *size / (c + 1);
+                }
+
+                sum
+            })
+            .collect();
+
+            tails.push(_TAIL(&keys, total));
+        }
+        tails.reverse();
+
+        Ok(Ranged { bounds, sizes, total, tails })
+    }
+
+    /// Return the number of keys
+    #[inline]
+    pub(crate) fn keyspace(&self) -> BigUint { self.tails[0][0].clone() }
+
+    /// Return the keyspace and the entropy of picking a composition uniformly, then one of its keys
+    #[inline]
+    pub(crate) fn entropy(&self) -> Entropy {
+        let (lo, hi) = self.total;
+        let log_fact: Vec<f64> = (0..=hi).scan(0f64, |x, i| { *x += (i.max(1) as f64).log2(); Some(*x) }).collect();
+
+        // cnts[r] is the number of compositions which sum to `r`, sums[r] is the sum of their logarithms,
+        // without the arrangements of the whole length, which are added at last
+        let mut cnts = vec![0f64; hi + 1];
+        let mut sums = vec![0f64; hi + 1];
+        cnts[0] = 1.0;
+
+        for ((min, max), size) in self.bounds.iter().zip(self.sizes).rev() {
+            let (mut next_cnts, mut next_sums) = (vec![0f64; hi + 1], vec![0f64; hi + 1]);
+
+            for r in 0..=hi {
+                for c in (*min..=r.min(*max)).filter(|c| cnts[r - c] > 0.0) {
+                    let log = if c > 0 { c as f64 * (*size as f64).log2() } else { 0.0 } - log_fact[c];
+
+                    next_cnts[r] += cnts[r - c];
+                    next_sums[r] += sums[r - c] + cnts[r - c] * log;
+                }
+            }
+
+            cnts = next_cnts;
+            sums = next_sums;
+        }
+
+        let (cnt, log_sum) =
+            (lo.min(hi + 1)..=hi).fold((0f64, 0f64), |(cnt, sum), t| (cnt + cnts[t], sum + sums[t] + cnts[t] * log_fact[t]));
+
+        Entropy::composed(self.keyspace(), cnt, log_sum)
+    }
+
+    /// Return the composition of the key at `idx`, and the index of the key among the ones of that composition
+    ///
+    /// The compositions are in the lexicographic order of their counts. It's `None` if `idx` isn't below the keyspace.
+    #[inline]
+    pub(crate) fn unrank(&self, idx: &BigUint) -> Option<(Vec<usize>, BigUint)> {
+        let mut left = idx.clone();
+
+        let cnts = self.walk(|_, _, keys| {
+            if left < *keys {
+                true
+            } else {
+                left -= keys;
+                false
+            }
+        })?;
+
+        Some((cnts, left))
+    }
+
+    /// Return the number of keys whose compositions come before `cnts`, the inverse of [`Ranged::unrank`]
+    ///
+    /// It's `None` if `cnts` has no key.
+    #[inline]
+    pub(crate) fn rank(&self, cnts: &[usize]) -> Option<BigUint> {
+        let mut offset = BigUint::zero();
+
+        self.walk(|j, c, keys| {
+            if c == cnts[j] {
+                true
+            } else {
+                offset += keys;
+                false
+            }
+        })?;
+
+        Some(offset)
+    }
+
+    /// Pick the count of every class in turn, `take(j, c, keys)` is called with every count `c` of class `j` in order
+    /// and the number of keys whose compositions start with the counts picked so far and `c`, until it returns `true`
+    #[inline]
+    fn walk(&self, mut take: impl FnMut(usize, usize, &BigUint) -> bool) -> Option<Vec<usize>> {
+        let mut cnts = Vec::with_capacity(self.bounds.len());
+        let mut len = 0;
+
+        // The arrangements of the counts picked so far, times the characters they pick from
+        let mut head = BigUint::one();
+
+        for (j, ((min, max), size)) in self.bounds.iter().zip(self.sizes).enumerate() {
+            let mut ways = BigUint::one();
+            let mut picked = None;
+
+            // `ways` is `C(len + c, c)` times `size` to the power of `c`
+            for c in 0..=(*max).min(self.total.1 - len) {
+                if c >= *min {
+                    let keys = &head * &ways * &self.tails[j + 1][len + c];
+
+                    if !keys.is_zero() && take(j, c, &keys) {
+                        picked = Some(c);
+                        break;
+                    }
+                }
+                ways = ways * (len + c + 1) * *size / (c + 1);
+            }
+
+            let c = picked?;
+            head *= ways;
+            len += c;
+            cnts.push(c);
+        }
+
+        Some(cnts)
+    }
+}
+
+
+/// Return the number of ways to finish a key which has `q` characters, for every `q`,
+/// from the number of keys of every length `keys` of the classes left
+///
+/// Both parts keep their own orders, so `r` more characters are arranged in `C(q + r, r)` ways.
+#[inline]
+fn _TAIL(keys: &[BigUint], (lo, hi): (usize, usize)) -> Vec<BigUint> {
+    (0..=hi).map(|q| {
+        let mut sum = BigUint::zero();
+        let mut ways = BigUint::one();
+
+        for (r, x) in keys[..=hi - q].iter().enumerate() {
+            if q + r >= lo {
+                sum += &ways * x;
+            }
+            ways = ways * (q + r + 1) / (r + 1);
+        }
+
+        sum
+    })
+    .collect()
+}
+
+
+/// Logarithm to base 2 of a big number
+#[inline]
+pub(crate) fn _LOG2(x: &BigUint) -> f64 {
+    let bits = x.bits();
+
+    let shift = bits.saturating_sub(64);
+
+    // The top 64 bits are enough for a `f64`
+    (x >> shift).to_u64().map_or(0.0, |x| x as f64).log2() + shift as f64
+}
@@ -5,22 +5,72 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum GenError {
-    #[error("The corresponding character is missing!")]
-    MissChar,
+    #[error("The class `{0}` requires characters, but has none!")]
+    MissChar(String),
 
-    #[error("Delete non-exist value!")]
-    DelNonExistValue,
+    #[error("Delete non-exist value: {0:?}!")]
+    DelNonExistValue(Vec<String>),
 
-    #[error("Require unit to be non-negative")]
+    #[error("Require unit to be positive")]
     InvalidUnit,
 
-    #[error("Require ASCII characters excluded control ones")]
-    InvalidChar,
+    #[error("Require single characters excluded control ones, got `{0}`")]
+    InvalidChar(String),
 
-    #[error("Require Non-negative integer in `&str`")]
-    InvalidNumber,
+    #[error("Require Non-negative integer, got `{0}`")]
+    InvalidNumber(String),
 
-    #[error("Require consistent field")]
-    InconsistentField,
-}
+    #[error("Require consistent field, `{0}` doesn't match")]
+    InconsistentField(String),
+
+    #[error("The class `{0}` requires characters, but has none once the look-alikes are skipped!")]
+    OnlyAmbiguousChar(String),
+
+    #[error("Require a range that some key can fit in")]
+    InvalidRange,
+
+    #[error("Require an upper bound of the length")]
+    UnboundedRange,
+
+    #[error("Require a ratio for every class, which weights some class with characters")]
+    InvalidRatio,
+
+    #[error("Require a finite entropy that the classes can reach")]
+    InvalidEntropy,
+
+    #[error("Require a wordlist of at least 2 distinct words")]
+    InvalidWordlist,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("No class is called `{0}`")]
+    UnknownClass(String),
 
+    #[error("A class called `{0}` already exists")]
+    DuplicateClass(String),
+
+    #[error("The count {0} exceeds the addressable memory")]
+    TooLarge(String),
+
+    #[error("Ranges are only counted up to a length of {max}, got {len}")]
+    LongRange { len: usize, max: usize },
+
+    #[error("Require an index below the keyspace, got `{0}`")]
+    InvalidIndex(String),
+
+    #[error("Require a mask of characters and known placeholders, got `{0}`")]
+    InvalidMask(String),
+
+    #[error("Require a regex of the supported syntax, got {0}")]
+    InvalidRegex(String),
+
+    #[error("No preset is called `{0}`")]
+    UnknownPreset(String),
+
+    #[error("The preset `{preset}` is invalid at `{field}`: {msg}")]
+    InvalidPolicy { preset: String, field: String, msg: String },
+
+    #[error("Require presets in TOML or JSON, {0}")]
+    InvalidPresets(String),
+}
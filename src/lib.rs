@@ -3,18 +3,22 @@
 //! ```rust
 //!     use rand_key::{RandKey, ToRandKey};
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let mut r_p = RandKey::new("10", "2", "3")?; // For now, it's empty. Use method `join` to generate the password
+//!     let mut r_p = RandKey::new(10, 2, 3)?;       // For now, it's empty. Use method `join` to generate the password
 //!     r_p.join()?;                                 // Now `r_p` has some content, be kept in its `key` field
-//!     println!("{}", r_p);                         // Print it on the screen
+//!     println!("{}", r_p.reveal());                // Print it on the screen
 //!     // One possible output: 7$pA7yMCw=2DPGN
 //!     // Or you can build from an existing `&str`
 //!     let mut r_p = "=tE)n5f`sidR>BV".to_randkey()?; // 10 letters, 4 symbols, 1 number
 //!     // You can rebuild a random password and with equivalent amount of letters, symbols and numbers. Like below
 //!     r_p.join()?;
-//!     println!("{}", r_p);
+//!     println!("{}", r_p.reveal());
 //!     // One possible output: qS`Xlyhpmg~"V8[
-//!     // But you have to make sure that they were composed of ASCII characters or it will return `Err`.
-//!     assert!("🦀️🦀️🦀️".to_randkey().is_err());
+//!     // Characters beyond ASCII work as well, they are counted as letters, symbols or numbers
+//!     let mut r_p = "Пароль🦀️2020".to_randkey()?;     // 6 letters, 1 symbol, 4 numbers
+//!     r_p.join()?;
+//!     // One possible output: о7Y🦀️0d2F2ьW
+//!     // Only control characters will get an `Err`
+//!     assert!("\u{7}".to_randkey().is_err());
 //! #   Ok(())
 //! # }
 //! ```
@@ -23,46 +27,105 @@
 //!
 //! If you want to generate a huge random password with 1 million letters, symbols and numbers each,
 //! our program will accept such a sequence: [1M, 1M, 1M].
-//! However, it takes up huge RAM(Because these numbers are represented in `BigUint`, kind of a `Vec`).
-//! And the procedure is single-threaded, you can only process them one by one.
-//!
-//! The approach is to divide these large numbers into many small numbers,
-//! and then process these small numbers in parallel,
-//! so the small numbers here can be understood as `UNIT`.
-//! For 1M(1 000 000) letters, we set 1K(1000) as the unit value, so [1M] = [1K, 1K, …, 1K] (1000 ones).
-//! And we just need to hand this sequence to [rayon](https://github.com/rayon-rs/rayon) for processing.
+//! The classes are laid out in a random order first, then the 3M positions are divided into chunks
+//! of `UNIT` characters, and the characters of every chunk are picked by
+//! [rayon](https://github.com/rayon-rs/rayon) in parallel, straight into one preallocated buffer.
+//! For 3M characters and 1K(1000) as the unit value, there are 3000 chunks.
 //! But the disadvantages are also obvious, if `UNIT` number is too small, like `1`,
-//! Threads did nothing useful! And capcity of the `Vec` is 1M at least!
-//! It will take up huge even all RAM and may harm your computer.
+//! Threads did nothing useful! If it's too large, there are fewer chunks than threads.
+//!
+//! `UNIT` is also the size of the chunks [`RandKey::join_to_writer`] writes.
 
 
 #![allow(non_snake_case)]
 #![deny(unused, dead_code)]
 
 
+mod builder;
+mod class;
+mod entropy;
 mod error;
+mod mask;
+mod phrase;
+#[cfg(feature = "serde")]
+mod policy;
 mod prelude;
+#[cfg(feature = "presets")]
+mod presets;
+mod regex;
+mod strength;
 mod utils;
+mod validate;
+
+
+pub use {
+    builder::RandKeyBuilder,
+    class::CharClass,
+    entropy::{Entropy, MAX_RANGED_LEN},
+    error::GenError,
+    mask::Mask,
+    phrase::{Case, RandPhrase, Wordlist},
+    regex::RandRegex,
+    strength::{Dictionaries, Dictionary, Match, Pattern, Strength},
+    utils::AMBIGUOUS,
+    validate::{Validator, Violation},
+    zeroize::Zeroizing,
+};
+
+#[cfg(feature = "presets")]
+pub use presets::Presets;
 
 
 use {
     utils::*,
-    error::GenError,
-    std::cell::RefCell,
+    class::Table,
+    entropy::*,
+    std::{borrow::Cow, collections::HashMap, io::Write, ops::RangeBounds, sync::atomic::{AtomicUsize, Ordering::Relaxed}},
+    parking_lot::RwLock,
+    zeroize::Zeroize,
+    unicode_segmentation::UnicodeSegmentation,
+    rand::rngs::OsRng,
+    rand_chacha::ChaCha20Rng,
     self::ASCIIExcludeCtrl::*,
-    crate::prelude::{AsBiguint, _DEFAULT_UNIT,}
+    crate::prelude::_DEFAULT_UNIT,
 };
 
 
 /// struct `RandKey`
-#[derive(Clone, Debug)]
+///
+/// It's `Send` and `Sync`, one configured generator can be shared across threads behind an `Arc`.
+/// `join` may run concurrently, the last one wins the `key` field,
+/// [`RandKey::generate`] hands every caller its own key instead.
+///
+/// The key and the buffers it's built in are wiped from memory when they are dropped or replaced.
+/// `Debug` and `Display` redact the key, print it with [`RandKey::reveal`].
 pub struct RandKey {
-    ltr_cnt: BigUint,
-    sbl_cnt: BigUint,
-    num_cnt: BigUint,
-    key:     RefCell<String>,
-    UNIT:    RefCell<BigUint>,
-    DATA:    Vec<Vec<String>>,
+    key:  RwLock<Zeroizing<String>>,
+    rng:  RngSource,
+    unambiguous: bool,
+    LEN:  Option<(usize, Option<usize>)>,
+    UNIT: AtomicUsize,
+    DATA: Vec<CharClass>,
+}
+
+
+/// The key of a `RandKey` or the passphrase of a `RandPhrase`, printed as it is
+///
+/// `Debug` and `Display` of the generators redact the secret, it's only printed through this wrapper.
+/// The secret is read when it's formatted, so keep the wrapper no longer than the formatting.
+pub struct Reveal<'a>(&'a RwLock<Zeroizing<String>>);
+
+
+/// The inclusive minimum and maximum of a count
+type Bounds = (usize, usize);
+
+
+/// A count of characters, like `10`, `10u64`, `"10"` or a `BigUint`
+///
+/// Every count has to fit in the addressable memory, or it's [`GenError::TooLarge`].
+pub trait Count {
+    /// Converts the value of `self` to a count
+    fn to_count(&self) -> Result<usize, GenError>;
 }
 
 
@@ -80,6 +143,8 @@ pub enum SetRandKeyOp {
 }
 
 
+/// The default classes of `RandKey`, usable wherever a class name is expected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ASCIIExcludeCtrl {
     Alphabetic,
     Punctuation,
@@ -87,47 +152,112 @@ pub enum ASCIIExcludeCtrl {
 }
 
 
+/// The source of randomness `RandKey::join` draws from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngSource {
+    /// `thread_rng()` on every worker thread, the default one
+    Thread,
+    /// The random number generator of the operating system
+    Os,
+    /// A ChaCha20 CSPRNG, freshly seeded from the operating system on every `join`
+    ChaCha20,
+    /// Deterministic generation from a master seed, see [`RandKey::join_seeded`]
+    Seeded([u8; 32]),
+}
+
+
+/// A cryptographically secure random number generator, which every `RngSource` gives
+pub(crate) trait CryptoRngCore: RngCore + CryptoRng {}
+
+impl<R: RngCore + CryptoRng> CryptoRngCore for R {}
+
+
+impl RngSource {
+    /// Call `f` with the random number generator of this source, the only place which turns a source into one
+    ///
+    /// `Seeded` gives a ChaCha20 stream from the seed, all the generators draw from it sequentially.
+    #[inline]
+    pub(crate) fn with_rng<T>(self, f: impl FnOnce(&mut dyn CryptoRngCore) -> T) -> T {
+        match self {
+            RngSource::Thread => f(&mut thread_rng()),
+            RngSource::Os => f(&mut OsRng),
+            RngSource::ChaCha20 => f(&mut ChaCha20Rng::from_entropy()),
+            RngSource::Seeded(seed) => f(&mut ChaCha20Rng::from_seed(seed)),
+        }
+    }
+}
+
+
 impl RandKey {
     /// Return an empty instance of `Result<RandKey, impl Error>`
+    ///
+    /// The counts are anything that implements [`Count`], numbers or strings of numbers.
     /// # Example
     ///
     /// Basic usage:
     /// ```
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// use rand_key::RandKey;
-    /// let mut r_p = RandKey::new("11", "4", "2")?;
+    /// use num_bigint::BigUint;
+    ///
+    /// let mut r_p = RandKey::new(11, 4, 2)?;
+    /// let mut r_p = RandKey::new(11u64, BigUint::from(4u32), "2")?;
+    ///
+    /// assert!(RandKey::new(-1, 0, 0).is_err());
     /// #   Ok(())
     /// # }
     /// ```
     #[inline]
     pub fn new<L, S, N>(ltr_cnt: L, sbl_cnt: S, num_cnt: N) -> Result<Self, GenError>
     where
-        L: AsRef<str>,
-        S: AsRef<str>,
-        N: AsRef<str>,
+        L: Count,
+        S: Count,
+        N: Count,
     {
-        if Self::check_init((&ltr_cnt, &sbl_cnt, &num_cnt)) {
-            Ok(RandKey {
-                ltr_cnt: ltr_cnt.as_biguint()?,
-                sbl_cnt: sbl_cnt.as_biguint()?,
-                num_cnt: num_cnt.as_biguint()?,
-                key:     RefCell::new(String::new()),
-                UNIT:    RefCell::new(BigUint::from(_DEFAULT_UNIT)),
-                DATA:    _DEFAULT_DATA(),
-            })
-        } else {
-            Err(GenError::InvalidNumber)
-        }
+        let cnts = vec![ltr_cnt.to_count()?, sbl_cnt.to_count()?, num_cnt.to_count()?];
+
+        Ok(RandKey {
+            key:  RwLock::new(Zeroizing::new(String::new())),
+            rng:  RngSource::Thread,
+            unambiguous: false,
+            LEN:  None,
+            UNIT: AtomicUsize::new(_DEFAULT_UNIT),
+            DATA: _DEFAULT_DATA().into_iter().zip(cnts).map(|(class, cnt)| CharClass { cnt, ..class }).collect(),
+        })
     }
 
+    /// Return a [`RandKeyBuilder`] of the default classes
     #[inline]
-    pub(crate) fn check_init<L, S, N>(input: (L, S, N)) -> bool
-    where
-        L: AsRef<str>,
-        S: AsRef<str>,
-        N: AsRef<str>,
-    {
-        input.0.as_biguint().is_ok() && input.1.as_biguint().is_ok() && input.2.as_biguint().is_ok()
+    pub fn builder() -> RandKeyBuilder { RandKeyBuilder::new() }
+
+    /// Return an empty `RandKey` of the default classes, whose keys have at least `bits` bits of entropy
+    ///
+    /// `ratio` asks for the counts of letters, symbols and numbers in the given proportion,
+    /// see [`RandKey::fit_entropy`].
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::with_entropy(128.0, None)?;
+    /// assert!(r_p.entropy()?.bits() >= 128.0);
+    ///
+    /// // Twice as many letters as symbols and numbers
+    /// let r_p = RandKey::with_entropy(128.0, Some([2, 1, 1]))?;
+    /// assert_eq!(r_p.get_cnt(Alphabetic), 12);
+    /// assert_eq!(r_p.get_cnt(Punctuation), 6);
+    /// assert_eq!(r_p.get_cnt(Digit), 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_entropy(bits: f64, ratio: Option<[usize; 3]>) -> Result<Self, GenError> {
+        let mut r_p = RandKey::default();
+        r_p.fit_entropy(bits, ratio.as_ref().map(|x| &x[..]))?;
+
+        Ok(r_p)
     }
 
     /// Return the key of random password in `&str`
@@ -143,13 +273,58 @@ impl RandKey {
     /// # }
     /// ```
     #[inline]
-    pub fn key(&self) -> String { self.key.clone().into_inner() }
+    pub fn key(&self) -> String { self.key.read().to_string() }
+
+    /// Return a copy of the key which is wiped from memory when dropped
+    ///
+    /// Prefer it to [`RandKey::key`] if the key shouldn't outlive its use.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// r_p.join()?;
+    ///
+    /// let secret = r_p.key_secret();
+    /// assert_eq!(secret.as_str(), r_p.key());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn key_secret(&self) -> Zeroizing<String> { self.key.read().clone() }
+
+    /// Return a wrapper which prints the key as it is
+    ///
+    /// `Debug` and `Display` of `RandKey` only print `<redacted>` in place of the key,
+    /// so it can't leak into logs by accident.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, SetRandKeyOp::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    /// r_p.set_key("]EH1zyqx3Bl/F8a", Check)?;
+    ///
+    /// assert_eq!(r_p.reveal().to_string(), "]EH1zyqx3Bl/F8a");
+    /// assert_eq!(r_p.to_string(), "<redacted>");
+    /// assert!(!format!("{:?}", r_p).contains("EH1"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn reveal(&self) -> Reveal<'_> { Reveal(&self.key) }
 
     /// Set the key of `RandKey`, depend on the name of operation.
     ///
     /// * **Update** : Replace the key you've passed and update the field.
     ///
     /// * **Check** : If the field of new value doesn't match the old one, it will return an `Err` or the old `key` will be replaced.
+    ///   With ranges, every count has to fall in its range instead.
     /// # Example
     ///
     /// Basic usage:
@@ -174,33 +349,28 @@ impl RandKey {
     pub fn set_key(&mut self, val: &str, op: SetRandKeyOp) -> Result<(), GenError> {
 
         use self::SetRandKeyOp::*;
-        let (val_ltr_cnt, val_sbl_cnt, val_num_cnt) = _CNT(val)?;
+        let cnts = _CNT(val, &self.DATA)?;
 
-        let mut mut_ref_key = self.key.borrow_mut();
+        let mut mut_ref_key = self.key.write();
 
         match op {
 
             Update => {
-                self.ltr_cnt = val_ltr_cnt;
-                self.sbl_cnt = val_sbl_cnt;
-                self.num_cnt = val_num_cnt;
-                *mut_ref_key = val.into();
+                self.DATA.iter_mut().zip(cnts).for_each(|(class, cnt)| {
+                    class.cnt = cnt;
+                    class.range = None;
+                });
+                self.LEN = None;
+                *mut_ref_key = Zeroizing::new(val.into());
 
                 Ok(())
             }
 
             Check => {
-                if (&self.ltr_cnt,
-                    &self.sbl_cnt,
-                    &self.num_cnt,) == (&val_ltr_cnt,
-                                        &val_sbl_cnt,
-                                        &val_num_cnt,) {
-                    *mut_ref_key = val.into();
-
-                    Ok(())
-                } else {
-                    Err(GenError::InconsistentField)
-                }
+                self.fits(&cnts)?;
+                *mut_ref_key = Zeroizing::new(val.into());
+
+                Ok(())
             }
 
         }
@@ -215,80 +385,265 @@ impl RandKey {
     /// use rand_key::RandKey;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// let r_p = RandKey::new(10, 2, 3)?;
     /// // The default value of unit is 1048576
-    /// assert_eq!(r_p.unit(), "1048576");
+    /// assert_eq!(r_p.unit(), 1048576);
+    ///
+    /// r_p.set_unit(1000)?;
+    /// assert_eq!(r_p.unit(), 1000);
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn unit(&self) -> &str { "1048576" }
+    pub fn unit(&self) -> usize { self.UNIT.load(Relaxed) }
 
     /// [set a right `UNIT` number](https://docs.rs/rand_pwd/1.1.3/rand_pwd/#the-unit-field).
     #[inline]
-    pub fn set_unit(&self, val: impl AsRef<str>) -> Result<(), GenError> {
-        let val = val.as_biguint()?;
-        let mut mut_ref_unit = self.UNIT.borrow_mut();
+    pub fn set_unit(&self, val: impl Count) -> Result<(), GenError> {
+        match val.to_count()? {
+            0 => Err(GenError::InvalidUnit),
+            val => {
+                self.UNIT.store(val, Relaxed);
+                Ok(())
+            }
+        }
+    }
+
+    /// Return the source of randomness used by `join`
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, RngSource};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// assert_eq!(r_p.rng(), RngSource::Thread);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn rng(&self) -> RngSource { self.rng }
+
+    /// Change the source of randomness used by `join`
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, RngSource};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    /// r_p.set_rng(RngSource::Os);
+    /// r_p.join()?;
+    /// assert_eq!(r_p.len(), 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_rng(&mut self, rng: RngSource) { self.rng = rng; }
+
+    /// Return whether `join` skips the characters which look alike
+    #[inline]
+    pub fn is_unambiguous(&self) -> bool { self.unambiguous }
 
-        if val == BigUint::zero() {
-            Err(GenError::InvalidUnit)
+    /// Skip or keep the characters which look alike in every class, they are the ones of [`AMBIGUOUS`]
+    ///
+    /// It's more than `0`/`O`, `1`/`l`/`I`, `5`/`S` and `` ` ``/`'`: `B` `8` `G` `6` `Q` `D` `Z` `2` `"` `|` `,` `.` `;` `:`
+    /// are skipped as well, so 43 letters, 24 symbols and only 4 digits are left, which lowers the entropy a lot.
+    /// The data are not touched, so the characters come back once it's turned off.
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, AMBIGUOUS, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("100", "20", "30")?;
+    /// r_p.set_unambiguous(true);
+    /// r_p.join()?;
+    /// assert!(!r_p.key().chars().any(|x| AMBIGUOUS.contains(&x.to_string().as_str())));
+    ///
+    /// // A class which has nothing but look-alikes can't be generated
+    /// r_p.set_unambiguous(false);
+    /// r_p.replace_data(&["a", ".", "0", "1"])?;
+    /// r_p.set_unambiguous(true);
+    /// assert!(r_p.join().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_unambiguous(&mut self, val: bool) { self.unambiguous = val; }
+
+    /// Return the classes `join` draws from, the look-alikes are filtered out in the unambiguous mode
+    #[inline]
+    pub(crate) fn active_data(&self) -> Cow<'_, [CharClass]> {
+        if self.unambiguous {
+            Cow::Owned(
+                self.DATA
+                    .iter()
+                    .map(|class| CharClass {
+                        data: Table::new(&class.data.iter().filter(|x| !AMBIGUOUS.contains(x)).collect::<Vec<_>>()),
+                        ..class.clone()
+                    })
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(&self.DATA)
+        }
+    }
+
+    /// Return all the classes `RandKey` carries
+    #[inline]
+    pub fn all_data(&self) -> &[CharClass] { &self.DATA }
+
+    /// Return the class of the given name
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// assert_eq!(r_p.class(Digit).unwrap().name(), "Digit");
+    /// assert!(r_p.class("Hex").is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn class(&self, kind: impl AsRef<str>) -> Option<&CharClass> {
+        self.DATA.iter().find(|class| class.name == kind.as_ref())
+    }
+
+    #[inline]
+    fn class_mut(&mut self, kind: impl AsRef<str>) -> Option<&mut CharClass> {
+        self.DATA.iter_mut().find(|class| class.name == kind.as_ref())
+    }
+
+    /// Add a class to `RandKey`
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, CharClass};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "0", "3")?;
+    /// r_p.add_class(CharClass::new("Space", &[" "], "2")?)?;
+    /// r_p.join()?;
+    /// assert_eq!(r_p.key().matches(' ').count(), 2);
+    ///
+    /// // The names of classes are unique
+    /// assert!(r_p.add_class(CharClass::new("Space", &[" "], "1")?).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn add_class(&mut self, class: CharClass) -> Result<(), GenError> {
+        if self.class(&class.name).is_some() {
+            Err(GenError::DuplicateClass(class.name))
         } else {
-            *mut_ref_unit = val;
+            self.DATA.push(class);
             Ok(())
         }
     }
 
-    /// Return the shared reference of `DATA`
+    /// Delete the class of the given name
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    /// r_p.del_class(Punctuation)?;
+    /// r_p.join()?;
+    /// assert_eq!(r_p.len(), 13);
+    /// # Ok(())
+    /// # }
+    /// ```
     #[inline]
-    pub fn all_data(&self) -> &Vec<Vec<String>> { &self.DATA }
+    pub fn del_class(&mut self, kind: impl AsRef<str>) -> Result<(), GenError> {
+        let len = self.DATA.len();
+        self.DATA.retain(|class| class.name != kind.as_ref());
+
+        if self.DATA.len() == len {
+            Err(GenError::UnknownClass(kind.as_ref().into()))
+        } else {
+            Ok(())
+        }
+    }
 
     /// Return data depend on given kind
+    /// # Example
+    ///
+    /// Basic Usage:
+    /// ```
+    /// use rand_key::{RandKey, GenError, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// assert_eq!(r_p.data(Digit)?.len(), 10);
+    /// assert!(matches!(r_p.data("Hex"), Err(GenError::UnknownClass(x)) if x == "Hex"));
+    /// # Ok(())
+    /// # }
+    /// ```
     #[inline]
-    pub fn data(&self, kind: ASCIIExcludeCtrl) -> &[String] {
-        match kind {
-            Alphabetic => &self.DATA[0],
-            Punctuation => &self.DATA[1],
-            Digit => &self.DATA[2],
+    pub fn data(&self, kind: impl AsRef<str>) -> Result<Vec<&str>, GenError> {
+        match self.class(&kind) {
+            Some(class) => Ok(class.data()),
+            None => Err(GenError::UnknownClass(kind.as_ref().into())),
         }
     }
 
     /// Clear all the data of `RandPwd`
     #[inline]
-    pub fn clear_all(&mut self) { self.DATA.iter_mut().for_each(|x| x.clear()); }
+    pub fn clear_all(&mut self) { self.DATA.iter_mut().for_each(|x| x.data.clear()); }
 
-    /// Clear the letters, symbols or numbers
+    /// Clear the data of the given class
     #[inline]
-    pub fn clear(&mut self, kind: ASCIIExcludeCtrl) {
-        match kind {
-            Alphabetic => self.DATA[0].clear(),
-            Punctuation => self.DATA[1].clear(),
-            Digit => self.DATA[2].clear(),
+    pub fn clear(&mut self, kind: impl AsRef<str>) -> Result<(), GenError> {
+        match self.class_mut(&kind) {
+            Some(class) => {
+                class.data.clear();
+                Ok(())
+            }
+            None => Err(GenError::UnknownClass(kind.as_ref().into())),
         }
     }
 
-    /// Check the data
+    /// Check the data, every class which requires some characters can't be empty
+    ///
+    /// A ranged class requires some if its minimum isn't zero, it's `MissChar` like an exact count.
     #[inline]
-    #[allow(non_snake_case)]
     pub(crate) fn check_data(&self) -> Result<(), GenError> {
-        let L = self.ltr_cnt.is_zero();
-        let S = self.sbl_cnt.is_zero();
-        let N = self.num_cnt.is_zero();
-
-        let dl = self.DATA[0].is_empty();
-        let ds = self.DATA[1].is_empty();
-        let dn = self.DATA[2].is_empty();
-
-        let dl_L = !L && dl;
-        let ds_S = !S && ds;
-        let dn_N = !N && dn;
+        let required = |class: &&CharClass| class.range.map_or(class.cnt, |x| x.0) > 0 && class.data.is_empty();
 
-        if !(dl_L || ds_S || dn_N) {
-            Ok(())
+        if let Some(class) = self.DATA.iter().find(required) {
+            Err(GenError::MissChar(class.name.clone()))
+        } else if let Some(class) = self.active_data().iter().find(required) {
+            Err(GenError::OnlyAmbiguousChar(class.name.clone()))
         } else {
-            Err(GenError::MissChar)
+            Ok(())
         }
     }
 
+    /// Put the grouped letters, symbols and numbers into the default classes, `op` decides how
+    #[inline]
+    fn regroup(&mut self, val: &[impl AsRef<str>], op: impl Fn(&mut CharClass, Vec<String>)) {
+        vec![Alphabetic, Punctuation, Digit].into_iter().zip(_GROUP(val)).for_each(|(kind, items)| {
+            match self.class_mut(kind) {
+                Some(class) => op(class, items),
+                None if !items.is_empty() => {
+                    self.DATA.push(CharClass { name: kind.as_ref().into(), cnt: 0, range: None, data: Table::new(&items) })
+                }
+                None => (),
+            }
+        });
+    }
+
     /// Delete the data
     /// # Example
     ///
@@ -300,34 +655,29 @@ impl RandKey {
     /// let mut r_p = RandKey::new("10", "2", "3")?;
     /// r_p.replace_data(&["1", "2", "a", "-"]);
     /// r_p.del_item(&["1"]);
-    /// assert_eq!(r_p.data(Digit), vec!["2"]);
+    /// assert_eq!(r_p.data(Digit)?, vec!["2"]);
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
     pub fn del_item(&mut self, items: &[impl AsRef<str>]) -> Result<(), GenError> {
 
-        let mut concat_data: Vec<String> = self.DATA.concat();
+        _CHECK_ITEM(items)?;
 
-        if _CHECK_ASCII(items) {
+        let items: Vec<&str> = items.iter().map(|x| x.as_ref()).collect();
 
-            let mut items: Vec<char> = items.iter().map(_CHAR_FROM_STR).collect();
-            items.dedup_by_key(|x| *x as u8);
+        if self.DATA.iter().any(|class| items.iter().any(|x| class.contains(x))) {
+            self.DATA.iter_mut().for_each(|class| class.data.retain(|x| !items.contains(&x)));
 
-            if items.iter().any(|x| concat_data.contains(&x.to_string())) {
-                concat_data.retain(|x| !items.contains(&_CHAR_FROM_STR(x)));
-                self.DATA = _GROUP(&concat_data);
-
-                Ok(())
-            } else {
-                Err(GenError::DelNonExistValue)
-            }
+            Ok(())
         } else {
-            Err(GenError::InvalidChar)
+            Err(GenError::DelNonExistValue(items.into_iter().map(String::from).collect()))
         }
     }
 
     /// Add data to the data set that `RandKey` carries
+    ///
+    /// Letters, symbols and numbers go to the `Alphabetic`, `Punctuation` and `Digit` classes.
     /// # Example
     ///
     /// Basic Usage:
@@ -339,29 +689,22 @@ impl RandKey {
     /// r_p.clear_all();
     /// r_p.add_item(&["a", "0", "-"]);
     /// r_p.join().unwrap();
-    /// println!("{}", r_p);
+    /// println!("{}", r_p.reveal());
     /// // One possible output: a0-0aaaaaa0-aaa
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    #[allow(clippy::needless_range_loop)]
     pub fn add_item(&mut self, val: &[impl AsRef<str>]) -> Result<(), GenError> {
-        if _CHECK_ASCII(val) {
-            let val = _GROUP(val);
-
-            for i in 0..self.DATA.len() {
-                self.DATA[i].extend_from_slice(&val[i]);
-                self.DATA[i].dedup_by_key(|x| _CHAR_FROM_STR(x) as u8);
-            }
+        _CHECK_ITEM(val)?;
+        self.regroup(val, |class, items| class.extend(&items));
 
-            Ok(())
-        } else {
-            Err(GenError::InvalidChar)
-        }
+        Ok(())
     }
 
     /// Return a new `RandKey` which has the replaced data
+    ///
+    /// Only the `Alphabetic`, `Punctuation` and `Digit` classes are replaced, the others are kept.
     /// # Example
     ///
     /// Basic usage:
@@ -376,122 +719,420 @@ impl RandKey {
     /// assert!(r_p.replace_data(&["-"]).is_err());
     /// assert!(r_p.replace_data(&["1", "a", "."]).is_ok());
     /// r_p.join()?;
-    /// println!("{}", r_p);
+    /// println!("{}", r_p.reveal());
     /// // One possible output: .aa1a1aaaa.a1aa
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    #[rustfmt::skip]
     pub fn replace_data(&mut self, val: &[impl AsRef<str>]) -> Result<(), GenError> {
+        _CHECK_ITEM(val)?;
+        self.regroup(val, |class, items| class.data = Table::new(&items));
+
+        self.check_data()
+    }
+
+    /// Returns the length of this `RandKey`, in characters, which are grapheme clusters.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    ///
+    /// r_p.join()?;
+    ///
+    /// assert_eq!(r_p.len(), 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize { self.key.read().graphemes(true).count() }
 
-        if _CHECK_ASCII(val) {
+    /// Returns true if this `RandKey` has a length of zero, and false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.key.read().is_empty() }
 
-            self.DATA = {
+    /// Get count of `RandKey`, a class that doesn't exist requires zero characters
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new(10, 2, 3)?;
+    ///
+    /// assert_eq!(r_p.get_cnt(Alphabetic), 10);
+    /// assert_eq!(r_p.get_cnt(Punctuation), 2);
+    /// assert_eq!(r_p.get_cnt(Digit), 3);
+    /// assert_eq!(r_p.get_cnt("Hex"), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn get_cnt(&self, kind: impl AsRef<str>) -> usize { self.class(kind).map_or(0, |class| class.cnt) }
 
-                let mut ltr = vec![];
-                let mut sbl = vec![];
-                let mut num = vec![];
+    /// Change the count of the given class of `RandKey`
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, CharClass, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    ///
+    /// // Set the letter's count
+    /// r_p.set_cnt(Alphabetic, 20)?;
+    /// assert_eq!(r_p.get_cnt(Alphabetic), 20);
+    ///
+    /// // Set the symbol's count
+    /// r_p.set_cnt(Punctuation, 1000u64)?;
+    /// assert_eq!(r_p.get_cnt(Punctuation), 1000);
+    ///
+    /// // Set the number's count, strings still work
+    /// r_p.set_cnt(Digit, "0")?;
+    /// assert_eq!(r_p.get_cnt(Digit), 0);
+    ///
+    /// // Set the count of your own class
+    /// r_p.add_class(CharClass::new("Space", &[" "], 0)?)?;
+    /// r_p.set_cnt("Space", 3)?;
+    /// assert_eq!(r_p.get_cnt("Space"), 3);
+    ///
+    /// assert!(r_p.set_cnt("Hex", 3).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_cnt(&mut self, kind: impl AsRef<str>, val: impl Count) -> Result<(), GenError> {
+        let val = val.to_count()?;
 
-                val.iter().for_each(|x| {
-                    let x = _CHAR_FROM_STR(x);
+        match self.class_mut(&kind) {
+            Some(class) => {
+                class.cnt = val;
+                class.range = None;
+                Ok(())
+            }
+            None => Err(GenError::UnknownClass(kind.as_ref().into())),
+        }
+    }
 
-                    if x.is_ascii_alphabetic()  { ltr.push(x.into()); }
-                    if x.is_ascii_punctuation() { sbl.push(x.into()); }
-                    if x.is_ascii_digit()       { num.push(x.into()); }
-                });
+    /// Return the range of count of the given class, `None` if it requires an exact count
+    ///
+    /// The maximum is `None` if the class is only bounded by the length of the key.
+    #[inline]
+    pub fn get_range(&self, kind: impl AsRef<str>) -> Option<(usize, Option<usize>)> {
+        self.class(kind).and_then(|class| class.range)
+    }
 
-                vec![ltr, sbl, num]
+    /// Let the count of the given class vary in `range`, instead of an exact count
+    ///
+    /// Every time `join` runs, it picks a composition uniformly among all the ones
+    /// that fit the ranges of classes and [the range of length](RandKey::set_len_range).
+    /// Classes without a range keep their exact counts, `set_cnt` turns the range off.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Length 16-24, at least 2 digits, at least 1 symbol, at most 4 symbols
+    /// let mut r_p = RandKey::new("0", "0", "0")?;
+    /// r_p.set_len_range(16..=24)?;
+    /// r_p.set_range(Alphabetic, ..)?;
+    /// r_p.set_range(Punctuation, 1..=4)?;
+    /// r_p.set_range(Digit, 2..)?;
+    /// r_p.join()?;
+    ///
+    /// let key = r_p.key();
+    /// assert!((16..=24).contains(&key.len()));
+    /// assert!((1..=4).contains(&key.chars().filter(char::is_ascii_punctuation).count()));
+    /// assert!(key.chars().filter(char::is_ascii_digit).count() >= 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_range(&mut self, kind: impl AsRef<str>, range: impl RangeBounds<usize>) -> Result<(), GenError> {
+        let range = _BOUNDS(range)?;
 
-            };
+        match self.class_mut(&kind) {
+            Some(class) => {
+                class.range = Some(range);
+                Ok(())
+            }
+            None => Err(GenError::UnknownClass(kind.as_ref().into())),
+        }
+    }
 
-            self.check_data()
+    /// Return the range of length of the key, `None` if it's not limited
+    #[inline]
+    pub fn len_range(&self) -> Option<(usize, Option<usize>)> { self.LEN }
 
+    /// Limit the length of the key to `range`, see [`RandKey::set_range`]
+    #[inline]
+    pub fn set_len_range(&mut self, range: impl RangeBounds<usize>) -> Result<(), GenError> {
+        self.LEN = Some(_BOUNDS(range)?);
+
+        Ok(())
+    }
+
+    /// Clear the range of length and the ranges of all classes, every class requires its exact count again
+    #[inline]
+    pub fn clear_range(&mut self) {
+        self.LEN = None;
+        self.DATA.iter_mut().for_each(|class| class.range = None);
+    }
+
+    /// Whether the counts of classes are picked from ranges on every `join`
+    #[inline]
+    fn is_ranged(&self) -> bool { self.LEN.is_some() || self.DATA.iter().any(|class| class.range.is_some()) }
+
+    /// Return the inclusive bounds of every class and of the length, which `_COMPOSE` works with
+    #[inline]
+    fn bounds(&self, DATA: &[CharClass]) -> Result<(Vec<Bounds>, Bounds), GenError> {
+        let bounds: Vec<_> = DATA.iter().map(|class| class.range.unwrap_or((class.cnt, Some(class.cnt)))).collect();
+
+        let (min, max) = self.LEN.unwrap_or((0, None));
+        let max = match max {
+            Some(max) => max,
+            None => bounds.iter().try_fold(0usize, |sum, x| x.1.and_then(|x| sum.checked_add(x))).ok_or(GenError::UnboundedRange)?,
+        };
+
+        let bounds =
+            DATA.iter()
+                .zip(bounds)
+                .map(|(class, (lo, hi))| (lo, if class.data.is_empty() { 0 } else { hi.unwrap_or(max).min(max) }))
+                .collect();
+
+        Ok((bounds, (min, max)))
+    }
+
+    /// Return the count of every class for this time of generation
+    #[inline]
+    fn composition<R: Rng + ?Sized>(&self, DATA: &[CharClass], rng: &mut R) -> Result<Vec<usize>, GenError> {
+        if self.is_ranged() {
+            let (bounds, total) = self.bounds(DATA)?;
+
+            _COMPOSE(&bounds, total, rng)
         } else {
-            Err(GenError::InvalidChar)
+            Ok(DATA.iter().map(|class| class.cnt).collect())
         }
     }
 
-    /// Returns the length of this `RandKey`, in both bytes and [char]s.
+    /// Return the keyspace and the entropy of the keys `join` generates
+    ///
+    /// Both come from the counts and the sizes of classes, the look-alikes are left out in the unambiguous mode.
+    /// See [`Entropy`] for the details. With exact counts it's exact for any length, millions of characters take seconds.
+    ///
+    /// With ranges, every length is counted on its own, so the exact figure is only given up to [`MAX_RANGED_LEN`]
+    /// characters. If the key may be longer, it's [`GenError::LongRange`], limit it with [`RandKey::set_len_range`].
     /// # Example
     ///
     /// Basic usage:
     /// ```
     /// use rand_key::RandKey;
-    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    /// use num_bigint::BigUint;
     ///
-    /// r_p.join()?;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("0", "0", "4")?;
+    /// let entropy = r_p.entropy()?;
+    /// assert_eq!(entropy.keyspace(), &BigUint::from(10_000u32));
+    /// assert!((entropy.bits() - 13.2877).abs() < 1e-4);
     ///
-    /// assert_eq!(&r_p.len(), "15");
+    /// // 1 letter and 1 number, the letter may come first or last
+    /// let r_p = RandKey::new("1", "0", "1")?;
+    /// assert_eq!(r_p.entropy()?.keyspace(), &BigUint::from(2u32 * 52 * 10));
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn len(&self) -> String { self.key.borrow().len().to_string() }
+    pub fn entropy(&self) -> Result<Entropy, GenError> {
+        self.check_data()?;
 
-    /// Returns true if this `RandKey` has a length of zero, and false otherwise.
+        let DATA = self.active_data();
+        let sizes: Vec<usize> = DATA.iter().map(|class| class.data.len()).collect();
+
+        if self.is_ranged() {
+            let (bounds, total) = self.bounds(&DATA)?;
+            Ok(Ranged::new(&bounds, total, &sizes)?.entropy())
+        } else {
+            let cnts: Vec<usize> = DATA.iter().map(|class| class.cnt).collect();
+            Entropy::exact(&cnts, &sizes)
+        }
+    }
+
+    /// Return the number of distinct keys `join` is able to generate, the same as the keyspace of [`RandKey::entropy`]
+    ///
+    /// Every index below it stands for exactly one key, see [`RandKey::unrank`].
     #[inline]
-    pub fn is_empty(&self) -> bool { self.key.borrow().is_empty() }
+    pub fn keyspace(&self) -> Result<BigUint, GenError> { self.entropy().map(|x| x.keyspace().clone()) }
 
-    /// Get count of `RandKey`
+    /// Return the key at `idx` of the keyspace, which is below [`RandKey::keyspace`]
+    ///
+    /// The keys are ordered by the composition in the ranged mode, then by the classes of the positions,
+    /// then by the characters in the order of the classes. Drawing a uniform index gives a uniform key of the keyspace.
+    /// Like [`RandKey::entropy`], ranges only work up to [`MAX_RANGED_LEN`] characters.
+    /// The time grows with the square of the length, tens of thousands of characters take about a second.
     /// # Example
     ///
     /// Basic usage:
     /// ```
-    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    /// use rand_key::RandKey;
+    /// use num_bigint::BigUint;
+    ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let r_p = RandKey::new("10", "2", "3")?;
+    /// let r_p = RandKey::new("1", "0", "1")?;
+    /// assert_eq!(r_p.keyspace()?, BigUint::from(2u32 * 52 * 10));
     ///
-    /// assert_eq!(&r_p.get_cnt(Alphabetic), "10");
-    /// assert_eq!(&r_p.get_cnt(Punctuation), "2");
-    /// assert_eq!(&r_p.get_cnt(Digit), "3");
+    /// assert_eq!(*r_p.unrank(&BigUint::from(0u8))?, "A0");
+    /// assert_eq!(*r_p.unrank(&BigUint::from(1039u32))?, "9z");
+    /// assert!(r_p.unrank(&BigUint::from(1040u32)).is_err());
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn get_cnt(&self, kind: ASCIIExcludeCtrl) -> String {
-        match kind {
-            Alphabetic => self.ltr_cnt.to_string(),
-            Punctuation => self.sbl_cnt.to_string(),
-            Digit => self.num_cnt.to_string(),
+    pub fn unrank(&self, idx: &BigUint) -> Result<Zeroizing<String>, GenError> {
+        self.check_data()?;
+
+        let DATA = self.active_data();
+        let sizes: Vec<usize> = DATA.iter().map(|class| class.data.len()).collect();
+
+        let (cnts, left) = if self.is_ranged() {
+            let (bounds, total) = self.bounds(&DATA)?;
+            Ranged::new(&bounds, total, &sizes)?.unrank(idx)
+        } else {
+            let cnts: Vec<usize> = DATA.iter().map(|class| class.cnt).collect();
+            Some((cnts, idx.clone())).filter(|(cnts, _)| matches!(_KEYSPACE(cnts, &sizes), Ok(x) if *idx < x))
         }
+        .ok_or_else(|| GenError::InvalidIndex(idx.to_string()))?;
+
+        let (classes, picks) = _UNRANK(&cnts, &sizes, &left)?;
+        let items: Vec<&str> = classes.iter().zip(picks.iter()).map(|(c, x)| DATA[*c].data.get(*x)).collect();
+
+        let mut key = Zeroizing::new(String::new());
+        key.try_reserve_exact(items.iter().map(|x| x.len()).sum()).map_err(|_| _TOO_LARGE(&cnts))?;
+        items.iter().for_each(|x| key.push_str(x));
+
+        Ok(key)
     }
 
-    /// Change the count of letters, symbols or numbers of `RandKey`
+    /// Return the index of `key` in the keyspace, the inverse of [`RandKey::unrank`]
+    ///
+    /// A character which is in more than one class counts for the first of them, like in [`RandKey::set_key`].
+    /// It takes about as long as [`RandKey::unrank`].
     /// # Example
     ///
     /// Basic usage:
     /// ```
-    /// use rand_key::{RandKey, ASCIIExcludeCtrl::*};
+    /// use rand_key::RandKey;
+    /// use num_bigint::BigUint;
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-    /// let mut r_p = RandKey::new("10", "2", "3")?;
+    /// let r_p = RandKey::new("2", "0", "1")?;
+    /// let key = r_p.unrank(&BigUint::from(2021u32))?;
+    /// assert_eq!(r_p.rank(&key)?, BigUint::from(2021u32));
     ///
-    /// // Set the letter's count
-    /// r_p.set_cnt(Alphabetic, "20");
-    /// assert_eq!(&r_p.get_cnt(Alphabetic), "20");
+    /// assert!(r_p.rank("ab").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn rank(&self, key: &str) -> Result<BigUint, GenError> {
+        self.check_data()?;
+
+        let DATA = self.active_data();
+        let sizes: Vec<usize> = DATA.iter().map(|class| class.data.len()).collect();
+
+        let lookup: HashMap<&str, (usize, usize)> =
+            DATA.iter()
+                .enumerate()
+                .rev()
+                .flat_map(|(i, class)| class.data.iter().enumerate().map(move |(j, x)| (x, (i, j))))
+                .collect();
+
+        let mut classes = Zeroizing::new(vec![]);
+        let mut picks = Zeroizing::new(vec![]);
+        let mut cnts = vec![0; DATA.len()];
+
+        for x in key.graphemes(true) {
+            let (i, j) = lookup.get(x).ok_or_else(|| GenError::InvalidChar(x.into()))?;
+            classes.push(*i);
+            picks.push(*j);
+            cnts[*i] += 1;
+        }
+
+        self.fits(&cnts)?;
+
+        let offset = if self.is_ranged() {
+            let (bounds, total) = self.bounds(&DATA)?;
+            Ranged::new(&bounds, total, &sizes)?.rank(&cnts).ok_or_else(|| GenError::InconsistentField("length".into()))?
+        } else {
+            BigUint::zero()
+        };
+
+        Ok(offset + _RANK(&cnts, &sizes, &classes, &picks)?)
+    }
+
+    /// Set the smallest counts of classes, whose keys have at least `bits` bits of entropy
     ///
-    /// // Set the symbol's count
-    /// r_p.set_cnt(Punctuation, "1000");
-    /// assert_eq!(&r_p.get_cnt(Punctuation), "1000");
+    /// Without `ratio`, the counts give the largest keyspace of the shortest length that meets `bits`.
+    /// With `ratio`, which has a weight for every class in the order of [`RandKey::all_data`],
+    /// the counts are the smallest multiple of it. Ranges are cleared, the counts are exact.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{RandKey, CharClass};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut r_p = RandKey::new("0", "0", "0")?;
+    /// r_p.add_class(CharClass::new("Space", &[" "], "0")?)?;
+    ///
+    /// // Only letters and numbers, plus exactly one space
+    /// r_p.fit_entropy(64.0, Some(&[4, 0, 1, 0]))?;
+    /// r_p.set_cnt("Space", "1")?;
+    /// r_p.join()?;
+    /// assert!(r_p.entropy()?.bits() >= 64.0);
     ///
-    /// // Set the number's count
-    /// r_p.set_cnt(Digit, "0");
-    /// assert_eq!(&r_p.get_cnt(Digit), "0");
+    /// assert!(r_p.fit_entropy(64.0, Some(&[1, 1])).is_err());
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    pub fn set_cnt(&mut self, kind: ASCIIExcludeCtrl, val: impl AsRef<str>) {
-        match kind {
-            Alphabetic => self.ltr_cnt = val.as_biguint().unwrap(),
-            Punctuation => self.sbl_cnt = val.as_biguint().unwrap(),
-            Digit => self.num_cnt = val.as_biguint().unwrap(),
+    pub fn fit_entropy(&mut self, bits: f64, ratio: Option<&[usize]>) -> Result<(), GenError> {
+        let sizes: Vec<usize> = self.active_data().iter().map(|class| class.data.len()).collect();
+        let cnts = _FIT(&sizes, bits, ratio)?;
+
+        self.clear_range();
+        self.DATA.iter_mut().zip(cnts).for_each(|(class, cnt)| class.cnt = cnt);
+
+        Ok(())
+    }
+
+    /// Check the counts of a key against the exact counts or the ranges, the error names the first class which doesn't fit
+    #[inline]
+    fn fits(&self, cnts: &[usize]) -> Result<(), GenError> {
+        let unfit = if self.is_ranged() {
+            let (bounds, (min, max)) = self.bounds(&self.DATA)?;
+
+            if (min..=max).contains(&cnts.iter().sum()) {
+                self.DATA.iter().zip(bounds.iter().zip(cnts)).find(|(_, ((lo, hi), cnt))| !(lo..=hi).contains(cnt)).map(|x| x.0)
+            } else {
+                return Err(GenError::InconsistentField("length".into()));
+            }
+        } else {
+            self.DATA.iter().zip(cnts).find(|(class, cnt)| class.cnt != **cnt).map(|x| x.0)
+        };
+
+        match unfit {
+            Some(class) => Err(GenError::InconsistentField(class.name.clone())),
+            None => Ok(()),
         }
     }
 
-    /// Generate the password for `RandKey`
+    /// Generate the password for `RandKey`, drawing from the [`RngSource`] it carries
     /// # Example
     ///
     /// Basic usage:
@@ -501,54 +1142,287 @@ impl RandKey {
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut r_p = RandKey::new("10", "2", "3")?;
     /// r_p.join()?;
-    /// println!("{}", r_p);
+    /// println!("{}", r_p.reveal());
     /// # Ok(())
     /// # }
     /// ```
     #[inline]
-    #[rustfmt::skip]
-    pub fn join(&self) -> Result<(), GenError> {
-
-        let mut inner = self.clone();
-
-        if inner.check_data().is_ok() {
-            let unit = &inner.UNIT.clone().into_inner();
-            let data = &inner.DATA;
-
-            // TODO: - Improve readability
-            let mut PWD =
-                vec![(&mut inner.ltr_cnt, &data[0]),
-                     (&mut inner.sbl_cnt, &data[1]),
-                     (&mut inner.num_cnt, &data[2]),]
-                    .into_iter()
-                    .map(|(bignum, data)| {
-                        _DIV_UNIT(unit, bignum)
-                            .par_iter()
-                            .map(|cnt| {
-                                _RAND_IDX(cnt, data.len())
-                                    .par_iter()
-                                    .map(|idx| data[*idx].clone())
-                                    .collect::<String>()
-                            })
-                            .collect()
-                    })
-                    .collect::<Vec<Vec<_>>>()
-                    .concat()
-                    .join("");
+    pub fn join(&self) -> Result<(), GenError> { self.store(self.generate()?) }
+
+    /// Generate a password and return it, the `key` field is left untouched
+    ///
+    /// It draws from the [`RngSource`] like `join`, and suits a generator shared across threads,
+    /// where every caller wants a key of its own. The key is wiped from memory when it is dropped.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    /// use std::{sync::Arc, thread};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = Arc::new(RandKey::new("10", "2", "3")?);
+    ///
+    /// let handles: Vec<_> =
+    ///     (0..4).map(|_| {
+    ///               let r_p = Arc::clone(&r_p);
+    ///               thread::spawn(move || r_p.generate())
+    ///           })
+    ///           .collect();
+    ///
+    /// for handle in handles {
+    ///     assert_eq!(handle.join().unwrap()?.len(), 15);
+    /// }
+    /// assert!(r_p.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn generate(&self) -> Result<Zeroizing<String>, GenError> {
+        // Both generate in parallel, the other sources draw sequentially
+        match self.rng {
+            RngSource::Thread => self.gen_par(|_| thread_rng(), &mut thread_rng()),
+            RngSource::Seeded(seed) => self.gen_seeded(seed),
+            rng => rng.with_rng(|mut rng| self.gen_with(&mut rng)),
+        }
+    }
+
+    /// Generate the password for `RandKey` with the given random number generator
+    ///
+    /// Both the characters and the final shuffle are drawn from `rng`,
+    /// so a deterministic generator gives a reproducible key.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha20Rng;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    ///
+    /// r_p.join_with(&mut ChaCha20Rng::seed_from_u64(42))?;
+    /// let first = r_p.key();
+    /// r_p.join_with(&mut ChaCha20Rng::seed_from_u64(42))?;
+    ///
+    /// assert_eq!(first, r_p.key());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn join_with<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), GenError> { self.store(self.gen_with(rng)?) }
 
-            // This is absolutely safe, because they are all ASCII characters except control ones.
-            let bytes = unsafe { PWD.as_bytes_mut() };
-            bytes.shuffle(&mut thread_rng());
+    /// Generate the password with the given random number generator, sequentially
+    #[inline]
+    fn gen_with<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<Zeroizing<String>, GenError> {
+        self.check_data()?;
 
-            let mut mut_ref_key = self.key.borrow_mut();
+        let DATA = self.active_data();
+        let cnts = self.composition(&DATA, rng)?;
+        let tables: Vec<&Table> = DATA.iter().map(|class| &class.data).collect();
 
-            *mut_ref_key = bytes.par_iter().map(|s| *s as char).collect::<String>();
+        let mut key = Zeroizing::new(String::new());
+        key.try_reserve_exact(_CAPACITY(&tables, &cnts)?).map_err(|_| _TOO_LARGE(&cnts))?;
+        Zeroizing::new(_LAYOUT(&cnts, rng)?).iter().for_each(|i| key.push_str(tables[*i as usize].pick(rng)));
 
-            Ok(())
+        Ok(key)
+    }
 
-        } else {
-            inner.check_data()
+    /// Generate the password straight into `w`, without keeping it in the `key` field
+    ///
+    /// The characters are written in chunks of `UNIT` characters, so the memory it takes is bounded
+    /// whatever the counts are. Each position picks its class with the probability in proportion to
+    /// the characters the class has left, which gives the same uniform shuffle as `join`.
+    /// It draws from the [`RngSource`] it carries on a single thread, with `Seeded` it's reproducible,
+    /// yet the key differs from the one of [`RandKey::join_seeded`].
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("100000", "20000", "30000")?;
+    ///
+    /// let mut out = Vec::new();
+    /// r_p.join_to_writer(&mut out)?;
+    ///
+    /// assert_eq!(out.len(), 150000);
+    /// assert_eq!(out.iter().filter(|x| x.is_ascii_digit()).count(), 30000);
+    /// assert!(r_p.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn join_to_writer(&self, w: impl Write) -> Result<(), GenError> {
+        self.rng.with_rng(|mut rng| self.join_to_writer_with(w, &mut rng))
+    }
+
+    /// Generate the password straight into `w` with the given random number generator, see [`RandKey::join_to_writer`]
+    #[inline]
+    pub fn join_to_writer_with<R: RngCore + CryptoRng>(&self, mut w: impl Write, rng: &mut R) -> Result<(), GenError> {
+
+        self.check_data()?;
+
+        let unit = self.unit_size();
+        let DATA = self.active_data();
+        let tables: Vec<&Table> = DATA.iter().map(|class| &class.data).collect();
+
+        let mut left = self.composition(&DATA, rng)?;
+        let mut total = left.iter().try_fold(0usize, |sum, x| sum.checked_add(*x)).ok_or_else(|| _TOO_LARGE(&left))?;
+
+        let max_width = tables.iter().map(|x| x.max_width()).max().unwrap_or(0);
+        let mut chunk = Zeroizing::new(String::new());
+        chunk.try_reserve_exact(unit.min(total).saturating_mul(max_width)).map_err(|_| _TOO_LARGE(&[unit.min(total)]))?;
+        let mut chunk_len = 0;
+
+        while total > 0 {
+            let i = _CLASS_AT(&left, _BELOW(total, rng));
+
+            chunk.push_str(tables[i].pick(rng));
+            chunk_len += 1;
+            left[i] -= 1;
+            total -= 1;
+
+            if chunk_len == unit || total == 0 {
+                w.write_all(chunk.as_bytes())?;
+                chunk.zeroize();
+                chunk_len = 0;
+            }
         }
+
+        w.flush()?;
+
+        Ok(())
     }
-}
 
+    /// Generate the password in parallel with a master seed
+    ///
+    /// Every `UNIT` chunk draws from its own ChaCha20 stream, derived from `seed` and the index of the chunk,
+    /// so the same seed, counts, data and `UNIT` give an identical key no matter how many threads rayon uses.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::RandKey;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let r_p = RandKey::new("10", "2", "3")?;
+    ///
+    /// r_p.join_seeded([7; 32])?;
+    /// let first = r_p.key();
+    /// r_p.join_seeded([7; 32])?;
+    ///
+    /// assert_eq!(first, r_p.key());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn join_seeded(&self, seed: [u8; 32]) -> Result<(), GenError> { self.store(self.gen_seeded(seed)?) }
+
+    /// Generate the password in parallel with a master seed, see [`RandKey::join_seeded`]
+    #[inline]
+    fn gen_seeded(&self, seed: [u8; 32]) -> Result<Zeroizing<String>, GenError> {
+        let stream = |idx| {
+            let mut rng = ChaCha20Rng::from_seed(seed);
+            rng.set_stream(idx);
+            rng
+        };
+
+        // Stream 0 is kept for the layout, chunks start from 1.
+        self.gen_par(|idx| stream(idx + 1), &mut stream(0))
+    }
+
+    /// Lay the classes out with `rng`, then pick the characters of every `UNIT` chunk in parallel
+    ///
+    /// `stream` returns the random number generator of a chunk from its index.
+    /// If all the characters have the same width, the chunks are written in place into one buffer.
+    #[inline]
+    #[rustfmt::skip]
+    fn gen_par<F, S, R>(&self, stream: F, rng: &mut R) -> Result<Zeroizing<String>, GenError>
+    where
+        F: Fn(u64) -> S + Sync,
+        S: Rng,
+        R: Rng + ?Sized,
+    {
+
+        self.check_data()?;
+
+        let unit = self.unit_size();
+        let DATA = self.active_data();
+        let cnts = self.composition(&DATA, rng)?;
+        let tables: Vec<&Table> = DATA.iter().map(|class| &class.data).collect();
+        let layout = Zeroizing::new(_LAYOUT(&cnts, rng)?);
+
+        let mut widths = tables.iter().zip(&cnts).filter(|(_, cnt)| **cnt > 0).map(|(table, _)| table.width());
+        let first = widths.next().flatten();
+
+        match first.filter(|w| widths.all(|x| x == Some(*w))) {
+
+            Some(w) => {
+                let len = _CAPACITY(&tables, &cnts)?;
+                let mut buf = Zeroizing::new(Vec::new());
+                buf.try_reserve_exact(len).map_err(|_| _TOO_LARGE(&cnts))?;
+                buf.resize(len, 0u8);
+
+                buf.par_chunks_mut(unit.saturating_mul(w))
+                   .zip(layout.par_chunks(unit))
+                   .enumerate()
+                   .for_each(|(idx, (out, ids))| {
+                       let mut rng = stream(idx as u64);
+                       let mut pick = |i: &u32| tables[*i as usize].pick(&mut rng).as_bytes();
+
+                       // Single bytes are stored directly, a `copy_from_slice` per character costs much more
+                       if w == 1 {
+                           out.iter_mut().zip(ids).for_each(|(x, i)| *x = pick(i)[0]);
+                       } else {
+                           out.chunks_mut(w).zip(ids).for_each(|(x, i)| x.copy_from_slice(pick(i)));
+                       }
+                   });
+
+                // The buffer is moved into the key, no copy of it is left behind
+                String::from_utf8(std::mem::take(&mut *buf)).map(Zeroizing::new).map_err(|e| {
+                    let err = GenError::InvalidChar(e.utf8_error().to_string());
+                    e.into_bytes().zeroize();
+                    err
+                })
+            }
+
+            None => {
+                let max_width = tables.iter().map(|x| x.max_width()).max().unwrap_or(0);
+                let chunks: Zeroizing<Vec<String>> = Zeroizing::new(
+                    layout.par_chunks(unit)
+                          .enumerate()
+                          .map(|(idx, ids)| {
+                              let mut rng = stream(idx as u64);
+                              let mut chunk = String::with_capacity(ids.len() * max_width);
+                              ids.iter().for_each(|i| chunk.push_str(tables[*i as usize].pick(&mut rng)));
+                              chunk
+                          })
+                          .collect()
+                );
+
+                let mut key = Zeroizing::new(String::new());
+                key.try_reserve_exact(chunks.iter().map(String::len).sum()).map_err(|_| _TOO_LARGE(&cnts))?;
+                chunks.iter().for_each(|x| key.push_str(x));
+
+                Ok(key)
+            }
+
+        }
+
+    }
+
+    /// Return the number of characters of a chunk
+    #[inline]
+    fn unit_size(&self) -> usize { self.UNIT.load(Relaxed).max(1) }
+
+    /// Store a generated password in the `key` field
+    #[inline]
+    fn store(&self, key: Zeroizing<String>) -> Result<(), GenError> {
+        *self.key.write() = key;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+use {
+    crate::{
+        RngSource,
+        class::{CharClass, Table},
+        entropy::Entropy,
+        error::GenError,
+        utils::*,
+    },
+    unicode_segmentation::UnicodeSegmentation,
+    zeroize::Zeroizing,
+};
+
+
+
+
+/// A key of a fixed shape, every position draws from its own charset
+///
+/// The positions are kept in place, unlike `RandKey::join` which shuffles the characters of all classes.
+/// A mask is written in the syntax of hashcat, every `?x` is a placeholder and the rest are literal characters:
+///
+/// | Placeholder | Charset                                              |
+/// |-------------|------------------------------------------------------|
+/// | `?l`        | `abcdefghijklmnopqrstuvwxyz`                         |
+/// | `?u`        | `ABCDEFGHIJKLMNOPQRSTUVWXYZ`                         |
+/// | `?d`        | `0123456789`, the `Digit` class                      |
+/// | `?s`        | the `Punctuation` class                              |
+/// | `?A`        | `?l?u`, the `Alphabetic` class                       |
+/// | `?a`        | `?l?u?d?s`                                           |
+/// | `?h`        | `0123456789abcdef`                                   |
+/// | `?H`        | `0123456789ABCDEF`                                   |
+/// | `??`        | a literal `?`                                        |
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::Mask;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mask = Mask::new("?u?l?l?l?d?d?s")?;
+/// let key = mask.generate()?;
+/// // One possible output: Kwex37%
+///
+/// let mut chars = key.chars();
+/// assert!(chars.next().unwrap().is_ascii_uppercase());
+/// assert!(chars.by_ref().take(3).all(|x| x.is_ascii_lowercase()));
+/// assert!(chars.by_ref().take(2).all(|x| x.is_ascii_digit()));
+/// assert!(chars.next().unwrap().is_ascii_punctuation());
+///
+/// // 26 * 26^3 * 10^2 * 32 keys
+/// assert_eq!(mask.entropy().keyspace().to_string(), "1462323200");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mask {
+    slots:    Vec<Slot>,
+    rng:      RngSource,
+    CHARSETS: Vec<CharClass>,
+}
+
+
+/// A position of a mask, either a literal character or the index of its charset
+#[derive(Clone, Debug)]
+enum Slot {
+    Literal(String),
+    Charset(usize),
+}
+
+
+impl Mask {
+    /// Parse a mask of the built-in placeholders, see [`Mask`]
+    #[inline]
+    pub fn new(pattern: &str) -> Result<Self, GenError> { Self::with_charsets(pattern, &[]) }
+
+    /// Parse a mask with custom placeholders besides the built-in ones
+    ///
+    /// Every custom charset is written like a mask, of literal characters and built-in placeholders,
+    /// and is used as `?` followed by its name, like `?1` of hashcat. Repeated characters are counted once.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::Mask;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mask = Mask::with_charsets("?1?1?1?1-?2?2", &[('1', "?u?d"), ('2', "xyz")])?;
+    /// let key = mask.generate()?;
+    /// // One possible output: Q7ZB-zx
+    ///
+    /// assert_eq!(key.len(), 7);
+    /// assert_eq!(&key[4..5], "-");
+    /// assert!(Mask::new("?1").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_charsets(pattern: &str, charsets: &[(char, &str)]) -> Result<Self, GenError> {
+        _PARSE(pattern, charsets, false)
+    }
+
+    /// Parse a template, where every character named in `placeholders` stands for its charset
+    ///
+    /// The charsets are written like in [`Mask::with_charsets`], the `?` placeholders work as well.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::Mask;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mask = Mask::template("LLLL-DDDD", &[('L', "?u"), ('D', "?d")])?;
+    /// let key = mask.generate()?;
+    /// // One possible output: QHVZ-0413
+    ///
+    /// assert!(key[..4].chars().all(|x| x.is_ascii_uppercase()));
+    /// assert!(key[5..].chars().all(|x| x.is_ascii_digit()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn template(pattern: &str, placeholders: &[(char, &str)]) -> Result<Self, GenError> {
+        _PARSE(pattern, placeholders, true)
+    }
+
+    /// Return the number of characters of the keys
+    #[inline]
+    pub fn len(&self) -> usize { self.slots.len() }
+
+    /// Whether the mask has no position
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.slots.is_empty() }
+
+    /// Return the source of randomness used by `generate`
+    #[inline]
+    pub fn rng(&self) -> RngSource { self.rng }
+
+    /// Change the source of randomness used by `generate`
+    #[inline]
+    pub fn set_rng(&mut self, rng: RngSource) { self.rng = rng; }
+
+    /// Return the keyspace and the entropy of the keys, which are all equally likely
+    ///
+    /// Unlike [`RandKey::entropy`], it never fails, every charset of a parsed mask has some characters.
+    ///
+    /// [`RandKey::entropy`]: crate::RandKey::entropy
+    #[inline]
+    pub fn entropy(&self) -> Entropy { Entropy::uniform(self.keyspace()) }
+
+    /// Return the number of distinct keys, every index below it stands for one key, see [`Mask::unrank`]
+    #[inline]
+    pub fn keyspace(&self) -> BigUint {
+        self.slots.iter().fold(BigUint::from(1u8), |keyspace, slot| match slot {
+            Slot::Literal(_) => keyspace,
+            Slot::Charset(i) => keyspace * self.CHARSETS[*i].data.len(),
+        })
+    }
+
+    /// Return the key at `idx` of the keyspace, which is below [`Mask::keyspace`]
+    ///
+    /// The keys are ordered by the characters of the placeholders, the first one is the most significant.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::Mask;
+    /// use num_bigint::BigUint;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mask = Mask::new("PIN-?d?d?d?d")?;
+    /// let key = mask.unrank(&BigUint::from(1234u32))?;
+    /// assert_eq!(*key, "PIN-1234");
+    /// assert_eq!(mask.rank(&key)?, BigUint::from(1234u32));
+    ///
+    /// assert!(mask.unrank(&mask.keyspace()).is_err());
+    /// assert!(mask.rank("PIN-12a4").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn unrank(&self, idx: &BigUint) -> Result<Zeroizing<String>, GenError> {
+        if *idx >= self.keyspace() {
+            return Err(GenError::InvalidIndex(idx.to_string()));
+        }
+
+        let mut left = idx.clone();
+        let mut items = vec![""; self.slots.len()];
+
+        for (item, slot) in items.iter_mut().zip(&self.slots).rev() {
+            *item = match slot {
+                Slot::Literal(x) => x,
+                Slot::Charset(i) => {
+                    let data = &self.CHARSETS[*i].data;
+                    let size = BigUint::from(data.len());
+                    let pick = (&left % &size).to_usize().unwrap_or_default();
+                    left /= size;
+                    data.get(pick)
+                }
+            };
+        }
+
+        let mut key = Zeroizing::new(String::new());
+        key.try_reserve_exact(items.iter().map(|x| x.len()).sum()).map_err(|_| _TOO_LARGE(&[self.slots.len()]))?;
+        items.iter().for_each(|x| key.push_str(x));
+
+        Ok(key)
+    }
+
+    /// Return the index of `key` in the keyspace, the inverse of [`Mask::unrank`]
+    ///
+    /// The error names the placeholder or the literal character which doesn't match.
+    #[inline]
+    pub fn rank(&self, key: &str) -> Result<BigUint, GenError> {
+        let items: Vec<&str> = key.graphemes(true).collect();
+
+        if items.len() != self.slots.len() {
+            return Err(GenError::InconsistentField("length".into()));
+        }
+
+        self.slots.iter().zip(items).try_fold(BigUint::zero(), |idx, (slot, x)| match slot {
+            Slot::Literal(y) if x == y => Ok(idx),
+            Slot::Literal(y) => Err(GenError::InconsistentField(y.clone())),
+            Slot::Charset(i) => {
+                let charset = &self.CHARSETS[*i];
+                let pick = charset.data.position(x).ok_or_else(|| GenError::InconsistentField(charset.name.clone()))?;
+                Ok(idx * charset.data.len() + pick)
+            }
+        })
+    }
+
+    /// Generate a key, drawing from the [`RngSource`] it carries
+    #[inline]
+    pub fn generate(&self) -> Result<Zeroizing<String>, GenError> {
+        self.rng.with_rng(|mut rng| self.generate_with(&mut rng))
+    }
+
+    /// Generate a key with the given random number generator
+    #[inline]
+    pub fn generate_with<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<Zeroizing<String>, GenError> {
+        let capacity = self.slots.iter().map(|slot| match slot {
+            Slot::Literal(x) => x.len(),
+            Slot::Charset(i) => self.CHARSETS[*i].data.max_width(),
+        });
+
+        let mut key = Zeroizing::new(String::new());
+        key.try_reserve_exact(capacity.sum()).map_err(|_| _TOO_LARGE(&[self.slots.len()]))?;
+
+        self.slots.iter().for_each(|slot| match slot {
+            Slot::Literal(x) => key.push_str(x),
+            Slot::Charset(i) => key.push_str(self.CHARSETS[*i].data.pick(rng)),
+        });
+
+        Ok(key)
+    }
+}
+
+
+/// Parse a mask, `bare` lets the characters named in `placeholders` stand for their charsets without `?`
+#[inline]
+#[rustfmt::skip]
+fn _PARSE(pattern: &str, placeholders: &[(char, &str)], bare: bool) -> Result<Mask, GenError> {
+
+    let mut CHARSETS: Vec<CharClass> = vec![];
+    let mut slots = vec![];
+
+    // The charsets are made on their first use, and shared by the positions which use them
+    let mut charset = |name: char| -> Result<usize, GenError> {
+        let key = format!("?{}", name);
+
+        if let Some(i) = CHARSETS.iter().position(|class| class.name == key) {
+            return Ok(i);
+        }
+
+        let data = match placeholders.iter().find(|(x, _)| *x == name) {
+            Some((_, def)) => _CHARSET(def)?,
+            None => Table::new(&_BUILTIN(name).ok_or_else(|| GenError::InvalidMask(key.clone()))?),
+        };
+
+        if data.is_empty() {
+            return Err(GenError::MissChar(key));
+        }
+
+        CHARSETS.push(CharClass { name: key, cnt: 0, range: None, data });
+        Ok(CHARSETS.len() - 1)
+    };
+
+    let mut graphemes = pattern.graphemes(true);
+
+    while let Some(x) = graphemes.next() {
+        let slot = match (x, _SINGLE(x)) {
+            ("?", _) => match graphemes.next() {
+                Some("?") => Slot::Literal("?".into()),
+                Some(y) => Slot::Charset(charset(_SINGLE(y).ok_or_else(|| GenError::InvalidMask(format!("?{}", y)))?)?),
+                None => return Err(GenError::InvalidMask("?".into())),
+            },
+            (_, Some(c)) if bare && placeholders.iter().any(|(x, _)| *x == c) => Slot::Charset(charset(c)?),
+            _ => {
+                _CHECK_ITEM(&[x])?;
+                Slot::Literal(x.into())
+            }
+        };
+
+        slots.push(slot);
+    }
+
+    Ok(Mask { slots, rng: RngSource::Thread, CHARSETS })
+
+}
+
+
+/// Parse the definition of a custom charset, of literal characters and built-in placeholders
+#[inline]
+fn _CHARSET(def: &str) -> Result<Table, GenError> {
+    let mut class = CharClass { name: String::new(), cnt: 0, range: None, data: Table::default() };
+    let mut graphemes = def.graphemes(true);
+
+    while let Some(x) = graphemes.next() {
+        match x {
+            "?" => match graphemes.next() {
+                Some("?") => class.extend(&["?"]),
+                Some(y) => class.extend(&_SINGLE(y).and_then(_BUILTIN).ok_or_else(|| GenError::InvalidMask(format!("?{}", y)))?),
+                None => return Err(GenError::InvalidMask("?".into())),
+            },
+            _ => {
+                _CHECK_ITEM(&[x])?;
+                class.extend(&[x]);
+            }
+        }
+    }
+
+    Ok(class.data)
+}
+
+
+/// Return the `char` of a grapheme cluster which is a single `char`
+#[inline]
+fn _SINGLE(x: &str) -> Option<char> {
+    let mut chars = x.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+
+/// The charset of a built-in placeholder
+#[inline]
+fn _BUILTIN(name: char) -> Option<Vec<String>> {
+    let ascii = |f: fn(&char) -> bool| (33..127u8).map(char::from).filter(f).map(String::from).collect();
+    let hex = |letters: &str| "0123456789".chars().chain(letters.chars()).map(String::from).collect();
+
+    let data = match name {
+        'l' => ascii(char::is_ascii_lowercase),
+        'u' => ascii(char::is_ascii_uppercase),
+        'd' => ascii(char::is_ascii_digit),
+        's' => ascii(char::is_ascii_punctuation),
+        'A' => ascii(char::is_ascii_alphabetic),
+        'a' => ascii(char::is_ascii_graphic),
+        'h' => hex("abcdef"),
+        'H' => hex("ABCDEF"),
+        _ => return None,
+    };
+
+    Some(data)
+}
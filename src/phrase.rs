@@ -0,0 +1,313 @@
+use {
+    crate::{
+        RngSource, Reveal,
+        class::Table,
+        entropy::Entropy,
+        error::GenError,
+        utils::*,
+    },
+    parking_lot::RwLock,
+    zeroize::Zeroizing,
+    std::{collections::HashSet, fs, path::Path},
+};
+
+
+
+
+/// A list of words to draw passphrases from
+#[derive(Clone, Debug)]
+pub struct Wordlist {
+    words: Vec<String>,
+}
+
+
+/// How the words of a passphrase are capitalized
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Case {
+    /// `correct horse`
+    Lower,
+    /// `CORRECT HORSE`
+    Upper,
+    /// `Correct Horse`
+    Title,
+    /// Every word is either lower case or title case, which adds 1 bit of entropy per word
+    Random,
+}
+
+
+/// struct `RandPhrase`, the passphrase counterpart of `RandKey`
+///
+/// It draws words uniformly from a [`Wordlist`], joins them with a separator,
+/// and optionally appends a digit and a symbol to random words.
+///
+/// The default list is the bundled English list of BIP-39, [`Wordlist::bip39`]: 2048 words, which is 11 bits per word.
+/// The only list bundled in the crate, others like the EFF ones are read with [`Wordlist::from_file`].
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{RandPhrase, Case};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut r_p = RandPhrase::new(6);
+/// r_p.set_separator("-");
+/// r_p.set_case(Case::Title);
+/// r_p.set_digit(true);
+/// r_p.join()?;
+/// println!("{}", r_p.reveal());
+/// // One possible output: Scatter-Jungle-Absorb7-Frame-Velvet-Oyster
+///
+/// // 6 words of 2048, and a digit after one of them
+/// assert!((r_p.entropy()?.bits() - (66.0 + 60f64.log2())).abs() < 1e-9);
+/// # Ok(())
+/// # }
+/// ```
+pub struct RandPhrase {
+    word_cnt:  usize,
+    separator: String,
+    case:      Case,
+    digit:     bool,
+    symbol:    bool,
+    rng:       RngSource,
+    phrase:    RwLock<Zeroizing<String>>,
+    WORDS:     Wordlist,
+}
+
+
+impl Clone for RandPhrase {
+    #[inline]
+    fn clone(&self) -> Self {
+        RandPhrase {
+            word_cnt:  self.word_cnt,
+            separator: self.separator.clone(),
+            case:      self.case,
+            digit:     self.digit,
+            symbol:    self.symbol,
+            rng:       self.rng,
+            phrase:    RwLock::new(self.phrase_secret()),
+            WORDS:     self.WORDS.clone(),
+        }
+    }
+}
+
+
+impl Wordlist {
+    /// The English wordlist of BIP-39, 2048 common words which differ in their first 4 letters, bundled in the crate
+    #[inline]
+    pub fn bip39() -> Self {
+        Wordlist { words: include_str!("../wordlists/bip39_english.txt").lines().map(String::from).collect() }
+    }
+
+    /// Read a wordlist from text, one word per line
+    ///
+    /// Lines in the diceware format, like `11111 abacus` of the EFF lists, are accepted as well,
+    /// the dice numbers are dropped. Blank lines and repeated words are skipped.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::Wordlist;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let list = Wordlist::parse("11111\tabacus\n11112\tabdomen\n\nabide\n")?;
+    /// assert_eq!(list.words(), ["abacus", "abdomen", "abide"]);
+    ///
+    /// // There is no choice with a single word
+    /// assert!(Wordlist::parse("abacus").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn parse(text: &str) -> Result<Self, GenError> {
+        let mut seen = HashSet::new();
+
+        let words: Vec<String> =
+            text.lines()
+                .filter_map(|line| {
+                    let line = line.trim();
+                    match line.split_once(char::is_whitespace) {
+                        Some((dice, word)) if dice.chars().all(|x| x.is_ascii_digit()) => Some(word.trim()),
+                        _ if line.is_empty() => None,
+                        _ => Some(line),
+                    }
+                })
+                .filter(|word| seen.insert(*word))
+                .map(String::from)
+                .collect();
+
+        if words.len() < 2 {
+            Err(GenError::InvalidWordlist)
+        } else {
+            Ok(Wordlist { words })
+        }
+    }
+
+    /// Read a wordlist from a file, see [`Wordlist::parse`] for its format
+    #[inline]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, GenError> { Self::parse(&fs::read_to_string(path)?) }
+
+    /// Return the words of the list
+    #[inline]
+    pub fn words(&self) -> &[String] { &self.words }
+
+    /// Return the number of words of the list
+    #[inline]
+    pub fn len(&self) -> usize { self.words.len() }
+
+    /// Returns true if the list has no word, which never happens to a parsed list
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.words.is_empty() }
+}
+
+
+impl RandPhrase {
+    /// Return an empty `RandPhrase` of `word_cnt` words from the bundled BIP-39 list, separated by spaces
+    #[inline]
+    pub fn new(word_cnt: usize) -> Self {
+        RandPhrase {
+            word_cnt,
+            separator: " ".into(),
+            case:      Case::Lower,
+            digit:     false,
+            symbol:    false,
+            rng:       RngSource::Thread,
+            phrase:    RwLock::new(Zeroizing::new(String::new())),
+            WORDS:     Wordlist::bip39(),
+        }
+    }
+
+    /// Return the passphrase
+    #[inline]
+    pub fn phrase(&self) -> String { self.phrase.read().to_string() }
+
+    /// Return a wrapper which prints the passphrase as it is, see [`RandKey::reveal`]
+    #[inline]
+    pub fn reveal(&self) -> Reveal<'_> { Reveal(&self.phrase) }
+
+    /// Return a copy of the passphrase which is wiped from memory when dropped
+    #[inline]
+    pub fn phrase_secret(&self) -> Zeroizing<String> { self.phrase.read().clone() }
+
+    /// Return the number of words
+    #[inline]
+    pub fn word_cnt(&self) -> usize { self.word_cnt }
+
+    /// Change the number of words
+    #[inline]
+    pub fn set_word_cnt(&mut self, val: usize) { self.word_cnt = val; }
+
+    /// Return the wordlist
+    #[inline]
+    pub fn wordlist(&self) -> &Wordlist { &self.WORDS }
+
+    /// Change the wordlist, like one of the EFF lists read by [`Wordlist::from_file`]
+    #[inline]
+    pub fn set_wordlist(&mut self, val: Wordlist) { self.WORDS = val; }
+
+    /// Change the separator between words
+    #[inline]
+    pub fn set_separator(&mut self, val: impl Into<String>) { self.separator = val.into(); }
+
+    /// Change the capitalization of words
+    #[inline]
+    pub fn set_case(&mut self, val: Case) { self.case = val; }
+
+    /// Append a random digit to a random word, or not
+    #[inline]
+    pub fn set_digit(&mut self, val: bool) { self.digit = val; }
+
+    /// Append a random symbol of the default `Punctuation` class to a random word, or not
+    #[inline]
+    pub fn set_symbol(&mut self, val: bool) { self.symbol = val; }
+
+    /// Change the source of randomness used by `join`
+    #[inline]
+    pub fn set_rng(&mut self, rng: RngSource) { self.rng = rng; }
+
+    /// Return the keyspace and the entropy of the passphrases `join` generates
+    ///
+    /// Words of the list are assumed to differ from each other after capitalization and injection.
+    #[inline]
+    pub fn entropy(&self) -> Result<Entropy, GenError> {
+        let n = self.word_cnt;
+        let mut keyspace = BigUint::from(self.WORDS.len()).pow(n.to_u32().ok_or_else(|| GenError::TooLarge(n.to_string()))?);
+
+        if self.case == Case::Random {
+            keyspace <<= n;
+        }
+        if self.digit && n > 0 {
+            keyspace *= 10 * n;
+        }
+        if self.symbol && n > 0 {
+            keyspace *= _SYMBOLS().len() * n;
+        }
+
+        Ok(Entropy::uniform(keyspace))
+    }
+
+    /// Generate the passphrase, drawing from the [`RngSource`] it carries
+    #[inline]
+    pub fn join(&self) -> Result<(), GenError> {
+        self.rng.with_rng(|mut rng| self.join_with(&mut rng))
+    }
+
+    /// Generate the passphrase with the given random number generator
+    #[inline]
+    pub fn join_with<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<(), GenError> {
+        let mut words: Zeroizing<Vec<String>> = Zeroizing::new(
+            (0..self.word_cnt)
+                .map(|_| {
+                    let word = self.WORDS.words[rng.gen_range(0, self.WORDS.len())].as_str();
+                    let cased = Zeroizing::new(match self.case {
+                        Case::Lower => word.to_lowercase(),
+                        Case::Upper => word.to_uppercase(),
+                        Case::Title => _TITLE(word),
+                        Case::Random => if rng.gen() { _TITLE(word) } else { word.to_lowercase() },
+                    });
+
+                    // Leave room for the digit and the symbol, so appending them never moves the word
+                    let mut x = String::with_capacity(cased.len() + 8);
+                    x.push_str(&cased);
+                    x
+                })
+                .collect()
+        );
+
+        if !words.is_empty() {
+            if self.digit {
+                let i = rng.gen_range(0, words.len());
+                words[i].push((b'0' + rng.gen_range(0u8, 10)) as char);
+            }
+            if self.symbol {
+                let i = rng.gen_range(0, words.len());
+                words[i].push_str(_SYMBOLS().pick(rng));
+            }
+        }
+
+        *self.phrase.write() = Zeroizing::new(words.join(&self.separator));
+
+        Ok(())
+    }
+}
+
+
+/// The symbols a passphrase may be injected with
+#[inline]
+fn _SYMBOLS() -> Table { _DEFAULT_DATA().swap_remove(1).data }
+
+
+/// Capitalize the first letter of a word
+#[inline]
+fn _TITLE(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => {
+            // Collecting into a growing `String` would leave the shorter copies behind
+            let mut title = String::with_capacity(word.len() * 3);
+            title.extend(first.to_uppercase().chain(chars.flat_map(char::to_lowercase)));
+            title
+        }
+        None => String::new(),
+    }
+}
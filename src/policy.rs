@@ -0,0 +1,210 @@
+use {
+    std::{borrow::Cow, convert::TryFrom, ops::Bound::{self, Included, Unbounded}, sync::atomic::Ordering::Relaxed},
+    serde::{de::Error, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer},
+    crate::{RandKey, RandKeyBuilder, RngSource, class::CharClass, error::GenError},
+};
+
+
+
+
+/// The policy of a `RandKey`, that is everything but the key
+///
+/// `exclude` is only read, the serialized classes have the exclusions applied already.
+/// A seed would rebuild the key, so it's neither written nor read, see [`Source`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Policy<'a> {
+    classes: Cow<'a, [CharClass]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    len: Option<Span>,
+    #[serde(default, deserialize_with = "_UNIT")]
+    unit: Option<usize>,
+    #[serde(default)]
+    unambiguous: bool,
+    #[serde(default)]
+    rng: Source,
+    #[serde(default, skip_serializing_if = "<[_]>::is_empty")]
+    exclude: Vec<String>,
+}
+
+
+/// The source of randomness as it's serialized, `RngSource::Seeded` has no counterpart
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum Source {
+    Thread,
+    Os,
+    ChaCha20,
+}
+
+
+impl Default for Source {
+    #[inline]
+    fn default() -> Self { Source::Thread }
+}
+
+
+impl TryFrom<RngSource> for Source {
+    type Error = &'static str;
+
+    #[inline]
+    fn try_from(rng: RngSource) -> Result<Self, Self::Error> {
+        match rng {
+            RngSource::Thread => Ok(Source::Thread),
+            RngSource::Os => Ok(Source::Os),
+            RngSource::ChaCha20 => Ok(Source::ChaCha20),
+            RngSource::Seeded(_) => Err("a seeded `RandKey` isn't serialized, the seed would rebuild its keys"),
+        }
+    }
+}
+
+
+impl From<Source> for RngSource {
+    #[inline]
+    fn from(rng: Source) -> Self {
+        match rng {
+            Source::Thread => RngSource::Thread,
+            Source::Os => RngSource::Os,
+            Source::ChaCha20 => RngSource::ChaCha20,
+        }
+    }
+}
+
+
+/// A class as it's serialized
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClassPolicy<'a> {
+    name: Cow<'a, str>,
+    data: Vec<Cow<'a, str>>,
+    #[serde(default)]
+    cnt: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    range: Option<Span>,
+}
+
+
+/// A range of counts, the maximum is left out if it's unbounded
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "RawSpan")]
+struct Span {
+    min: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<usize>,
+}
+
+
+/// A range of counts as it's written, which may be empty
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSpan {
+    #[serde(default)]
+    min: usize,
+    #[serde(default)]
+    max: Option<usize>,
+}
+
+
+impl Span {
+    #[inline]
+    fn bounds(self) -> (Bound<usize>, Bound<usize>) { (Included(self.min), self.max.map_or(Unbounded, Included)) }
+}
+
+
+impl TryFrom<RawSpan> for Span {
+    type Error = GenError;
+
+    #[inline]
+    fn try_from(RawSpan { min, max }: RawSpan) -> Result<Self, GenError> {
+        match max {
+            Some(max) if min > max => Err(GenError::InvalidRange),
+            _ => Ok(Span { min, max }),
+        }
+    }
+}
+
+
+impl From<(usize, Option<usize>)> for Span {
+    #[inline]
+    fn from((min, max): (usize, Option<usize>)) -> Self { Span { min, max } }
+}
+
+
+impl Serialize for CharClass {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ClassPolicy {
+            name:  Cow::Borrowed(&self.name),
+            data:  self.data.iter().map(Cow::Borrowed).collect(),
+            cnt:   self.cnt,
+            range: self.range.map(Span::from),
+        }
+        .serialize(serializer)
+    }
+}
+
+
+impl<'de> Deserialize<'de> for CharClass {
+    /// The characters are checked like in [`CharClass::new`]
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ClassPolicy { name, data, cnt, range } = ClassPolicy::deserialize(deserializer)?;
+        let mut class = CharClass::new(name, &data, cnt).map_err(D::Error::custom)?;
+        class.range = range.map(|x| (x.min, x.max));
+
+        Ok(class)
+    }
+}
+
+
+impl Serialize for RandKey {
+    /// Only the policy is serialized, never the key
+    ///
+    /// It's an error if the source of randomness is `RngSource::Seeded`, whose seed would rebuild the key.
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rng = Source::try_from(self.rng).map_err(S::Error::custom)?;
+
+        Policy {
+            classes:     Cow::Borrowed(&self.DATA),
+            len:         self.LEN.map(Span::from),
+            unit:        Some(self.UNIT.load(Relaxed)),
+            unambiguous: self.unambiguous,
+            rng,
+            exclude:     vec![],
+        }
+        .serialize(serializer)
+    }
+}
+
+
+impl<'de> Deserialize<'de> for RandKey {
+    /// The policy is validated like in [`RandKeyBuilder::build`], the key is empty
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Policy { classes, len, unit, unambiguous, rng, exclude } = Policy::deserialize(deserializer)?;
+
+        let mut builder = RandKeyBuilder::empty().unambiguous(unambiguous).rng(rng.into()).exclude(&exclude);
+
+        for class in classes.into_owned() {
+            builder = builder.class(class);
+        }
+        if let Some(len) = len {
+            builder = builder.len_range(len.bounds());
+        }
+        if let Some(unit) = unit {
+            builder = builder.unit(unit);
+        }
+
+        builder.build().map_err(D::Error::custom)
+    }
+}
+
+
+/// Read `UNIT`, which has to be positive
+#[inline]
+fn _UNIT<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    match usize::deserialize(deserializer)? {
+        0 => Err(D::Error::custom(GenError::InvalidUnit)),
+        unit => Ok(Some(unit)),
+    }
+}
@@ -1,13 +1,16 @@
 use {
+    unicode_segmentation::UnicodeSegmentation,
     std::{
-        cell::RefCell,
-        fmt::{self, Display, Formatter},
+        fmt::{self, Debug, Display, Formatter},
+        sync::atomic::{AtomicUsize, Ordering::Relaxed},
     },
+    parking_lot::RwLock,
     crate::{
         error::GenError,
-        RandKey, ToRandKey,
+        Count, RandKey, RandPhrase, Reveal, ToRandKey, RngSource, Violation,
+        ASCIIExcludeCtrl::{self, *},
         SetRandKeyOp::Update,
-        utils::{_DEFAULT_DATA, BigUint},
+        utils::{_DEFAULT_DATA, BigUint, ToBigUint, ToPrimitive},
     },
 };
 
@@ -28,35 +31,166 @@ impl<T: AsRef<str>> AsBiguint for T {
 
     #[inline]
     fn as_biguint(&self) -> Self::Output {
-        let convert = self.as_ref().parse::<BigUint>();
-        if let Ok(result) = convert {
-            Ok(result)
-        } else {
-            Err(GenError::InvalidNumber)
-        }
+        self.as_ref().parse::<BigUint>().map_err(|_| GenError::InvalidNumber(self.as_ref().into()))
     }
 }
 
 
+impl Count for BigUint {
+    #[inline]
+    fn to_count(&self) -> Result<usize, GenError> {
+        self.to_usize().filter(|x| *x <= isize::MAX as usize).ok_or_else(|| GenError::TooLarge(self.to_string()))
+    }
+}
+
+
+/// Strings are parsed as non-negative integers, the thin adapter of the old `&str` API
+impl Count for str {
+    #[inline]
+    fn to_count(&self) -> Result<usize, GenError> { self.as_biguint()?.to_count() }
+}
+
+
+impl Count for String {
+    #[inline]
+    fn to_count(&self) -> Result<usize, GenError> { self.as_str().to_count() }
+}
+
+
+impl<T: Count + ?Sized> Count for &T {
+    #[inline]
+    fn to_count(&self) -> Result<usize, GenError> { (**self).to_count() }
+}
+
+
+macro_rules! impl_count {
+    ($($t:ty),*) => {
+        $(
+            /// Negative numbers are invalid
+            impl Count for $t {
+                #[inline]
+                fn to_count(&self) -> Result<usize, GenError> {
+                    self.to_biguint().ok_or_else(|| GenError::InvalidNumber(self.to_string()))?.to_count()
+                }
+            }
+        )*
+    };
+}
+
+impl_count!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+
 impl Default for RandKey {
     /// The default value of `RandKey`
     #[inline]
     fn default() -> Self {
         RandKey {
-            ltr_cnt: Default::default(),
-            sbl_cnt: Default::default(),
-            num_cnt: Default::default(),
-            key:     Default::default(),
-            UNIT:    RefCell::new(BigUint::from(_DEFAULT_UNIT)),
-            DATA:    _DEFAULT_DATA(),
+            key:  Default::default(),
+            rng:  RngSource::Thread,
+            unambiguous: false,
+            LEN:  None,
+            UNIT: AtomicUsize::new(_DEFAULT_UNIT),
+            DATA: _DEFAULT_DATA(),
+        }
+    }
+}
+
+
+impl Default for RngSource {
+    /// `RngSource::Thread`, which `RandKey::new` starts with
+    #[inline]
+    fn default() -> Self { RngSource::Thread }
+}
+
+
+impl Clone for RandKey {
+    #[inline]
+    fn clone(&self) -> Self {
+        RandKey {
+            key:  RwLock::new(self.key_secret()),
+            rng:  self.rng,
+            unambiguous: self.unambiguous,
+            LEN:  self.LEN,
+            UNIT: AtomicUsize::new(self.UNIT.load(Relaxed)),
+            DATA: self.DATA.clone(),
         }
     }
 }
 
 
+impl AsRef<str> for ASCIIExcludeCtrl {
+    /// The name of the default class
+    #[inline]
+    fn as_ref(&self) -> &str {
+        match self {
+            Alphabetic => "Alphabetic",
+            Punctuation => "Punctuation",
+            Digit => "Digit",
+        }
+    }
+}
+
+
+impl Debug for RandKey {
+    /// Only the length of the key and the counts of the classes, the key is redacted
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandKey")
+         .field("key", &format_args!("<redacted>"))
+         .field("len", &self.len())
+         .field("cnts", &self.DATA.iter().map(|class| (class.name(), class.cnt)).collect::<Vec<_>>())
+         .finish()
+    }
+}
+
+
+impl Debug for RandPhrase {
+    /// Only the number of words, the passphrase is redacted
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RandPhrase")
+         .field("phrase", &format_args!("<redacted>"))
+         .field("word_cnt", &self.word_cnt())
+         .finish()
+    }
+}
+
+
 impl Display for RandKey {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "\n{}\n", self.key.borrow()) }
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str("<redacted>") }
+}
+
+
+impl Display for RandPhrase {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str("<redacted>") }
+}
+
+
+impl Display for Reveal<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(self.0.read().as_str()) }
+}
+
+
+impl Display for Violation {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let range = |min: &usize, max: &Option<usize>| match max {
+            Some(max) if max == min => format!("exactly {}", min),
+            Some(max) => format!("between {} and {}", min, max),
+            None => format!("at least {}", min),
+        };
+
+        match self {
+            Violation::Length { len, min, max } => write!(f, "The length is {}, but it has to be {}", len, range(min, max)),
+            Violation::Count { class, cnt, min, max } => write!(f, "It has {} of `{}`, but requires {}", cnt, class, range(min, max)),
+            Violation::NotAllowed(x) => write!(f, "`{}` is not an allowed character", x),
+            Violation::Forbidden(x) => write!(f, "`{}` is a forbidden character", x),
+            Violation::Repeat { item, run, max } => write!(f, "`{}` is repeated {} times in a row, at most {} are allowed", item, run, max),
+        }
+    }
 }
 
 
@@ -64,11 +198,13 @@ impl<T: AsRef<str>> ToRandKey for T {
     #[inline]
     fn to_randkey(&self) -> Result<RandKey, GenError> {
         let mut r_p: RandKey = Default::default();
-        if r_p.set_key(self.as_ref(), Update).is_ok() {
-            Ok(r_p)
-        } else {
-            Err(GenError::InvalidChar)
-        }
+        let items: Vec<&str> = self.as_ref().graphemes(true).collect();
+
+        // Characters out of the default data join the class they look like
+        r_p.add_item(&items)?;
+        r_p.set_key(self.as_ref(), Update)?;
+
+        Ok(r_p)
     }
 }
 
@@ -0,0 +1,135 @@
+use {
+    std::{collections::BTreeMap, fs, path::Path},
+    serde::Deserializer,
+    crate::{RandKey, error::GenError},
+};
+
+
+
+
+/// A set of named policies, like `db_password`, `api_token` or `wifi`, read from a TOML or JSON file
+///
+/// Every policy has the fields `RandKey` is serialized with, all of them are validated when the file is read.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{Presets, ASCIIExcludeCtrl::*};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let presets = Presets::from_toml(r#"
+///     [wifi]
+///     classes = [
+///         { name = "Alphabetic", data = ["a", "b", "c", "x", "y", "z"], cnt = 12 },
+///         { name = "Digit", data = ["2", "3", "4", "5", "6", "7", "8", "9"], cnt = 4 },
+///     ]
+///
+///     [api_token]
+///     classes = [{ name = "Hex", data = ["0", "1", "2", "3", "4", "5", "6", "7",
+///                                        "8", "9", "a", "b", "c", "d", "e", "f"], cnt = 32 }]
+///     rng = "Os"
+/// "#)?;
+///
+/// let r_p = presets.get("wifi")?;
+/// r_p.join()?;
+/// assert_eq!(r_p.len(), 16);
+/// assert_eq!(r_p.get_cnt(Digit), 4);
+///
+/// assert_eq!(presets.names().collect::<Vec<_>>(), ["api_token", "wifi"]);
+/// assert!(presets.get("db_password").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Presets {
+    policies: BTreeMap<String, RandKey>,
+}
+
+
+impl Presets {
+    /// Read the presets from TOML, every top-level table is a policy
+    ///
+    /// An invalid policy is reported as [`GenError::InvalidPolicy`], with its name and the path of the field.
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::{Presets, GenError};
+    ///
+    /// let err = Presets::from_toml(r#"
+    ///     [db_password]
+    ///     classes = [{ name = "Digit", data = ["1", "22"], cnt = 4 }]
+    /// "#).unwrap_err();
+    ///
+    /// assert!(matches!(err, GenError::InvalidPolicy { preset, field, .. }
+    ///                       if preset == "db_password" && field == "classes[0]"));
+    /// ```
+    #[inline]
+    pub fn from_toml(text: &str) -> Result<Self, GenError> {
+        let tables: BTreeMap<String, toml::Value> = toml::from_str(text).map_err(|e| GenError::InvalidPresets(e.to_string()))?;
+
+        Self::parse(tables)
+    }
+
+    /// Read the presets from JSON, every key of the top-level object is a policy, see [`Presets::from_toml`]
+    #[inline]
+    pub fn from_json(text: &str) -> Result<Self, GenError> {
+        let objects: BTreeMap<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| GenError::InvalidPresets(e.to_string()))?;
+
+        Self::parse(objects)
+    }
+
+    /// Read the presets from a file, JSON if its extension is `json`, TOML otherwise
+    #[inline]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, GenError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+
+        match path.extension() {
+            Some(ext) if ext == "json" => Self::from_json(&text),
+            _ => Self::from_toml(&text),
+        }
+    }
+
+    /// Return a new `RandKey` of the policy called `name`
+    #[inline]
+    pub fn get(&self, name: impl AsRef<str>) -> Result<RandKey, GenError> {
+        self.policies
+            .get(name.as_ref())
+            .cloned()
+            .ok_or_else(|| GenError::UnknownPreset(name.as_ref().into()))
+    }
+
+    /// Return the names of the policies, in order
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> { self.policies.keys().map(String::as_str) }
+
+    /// Validate every policy, the first invalid one is returned in the error
+    #[inline]
+    fn parse<'de, D>(values: BTreeMap<String, D>) -> Result<Self, GenError>
+    where
+        D: Deserializer<'de>,
+    {
+        let policies =
+            values.into_iter()
+                  .map(|(name, value)| {
+                      let r_p = _POLICY(value).map_err(|(field, msg)| GenError::InvalidPolicy {
+                          preset: name.clone(),
+                          field,
+                          msg,
+                      })?;
+                      Ok((name, r_p))
+                  })
+                  .collect::<Result<_, GenError>>()?;
+
+        Ok(Presets { policies })
+    }
+}
+
+
+/// Read a policy, the path of the field is returned along with the message if it's invalid
+#[inline]
+fn _POLICY<'de, D: Deserializer<'de>>(value: D) -> Result<RandKey, (String, String)> {
+    serde_path_to_error::deserialize(value).map_err(|e| (e.path().to_string(), e.inner().to_string()))
+}
@@ -0,0 +1,683 @@
+use {
+    crate::{
+        RngSource,
+        entropy::Entropy,
+        error::GenError,
+        utils::*,
+    },
+    num_bigint::RandBigInt,
+    std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    zeroize::Zeroizing,
+};
+
+
+
+
+/// Strings which match a regular expression, drawn uniformly
+///
+/// A bounded subset of the regex syntax is supported:
+///
+/// * Literal characters, and punctuation escaped by `\`, like `\.` or `\{`
+/// * `.`, which is any printable ASCII character, the space included
+/// * `\d`, `\w`, `\s` (a space) and their negations `\D`, `\W`, `\S`
+/// * Classes like `[a-z0-9_]` or `[^"'\\]`, negated classes are taken within printable ASCII
+/// * Groups `(...)` and `(?:...)`, and alternation `|`
+/// * Repetitions `?`, `{n}`, `{n,m}` and `{,m}`, lazy ones like `{n,m}?` generate the same strings
+/// * `^` at the beginning and `$` at the end, which are implied anyway
+///
+/// Unbounded repetitions, like `*`, `+` and `{n,}`, and other constructs like `\b`, back references
+/// or lookarounds are rejected with [`GenError::InvalidRegex`]. So is a regex which takes more than
+/// 65536 steps to generate a string, where every character, group and repetition counts as a step.
+///
+/// Every matching string is equally likely, and the keyspace counts the distinct strings.
+/// A string which matches in more than one way, like `a` of `a|[ab]` or of `a?a?`, is counted and drawn once.
+/// The regex is turned into a deterministic automaton, whose states count the strings which finish from them.
+/// A regex whose automaton would be too large, like `(a?){5000}`, or whose counts would take too much memory,
+/// like `.{20000}`, is rejected with [`GenError::InvalidRegex`] as well.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::RandRegex;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let regex = RandRegex::new("[A-Z]{2}[0-9]{6}[a-z!@#]{4}")?;
+/// let key = regex.generate()?;
+/// // One possible output: QK048213x!ab
+///
+/// assert_eq!(key.len(), 12);
+/// assert!(key[..2].chars().all(|x| x.is_ascii_uppercase()));
+/// assert!(key[2..8].chars().all(|x| x.is_ascii_digit()));
+///
+/// // 26^2 * 10^6 * 29^4 strings
+/// assert_eq!(regex.entropy().keyspace().to_string(), "478121956000000");
+///
+/// // `a`, `aa` and the empty string
+/// assert_eq!(RandRegex::new("a?a?")?.entropy().keyspace().to_string(), "3");
+///
+/// assert!(RandRegex::new("[a-z]+").is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RandRegex {
+    dfa:   Dfa,
+    width: usize,
+    rng:   RngSource,
+}
+
+
+/// A node of the parsed regex, with the maximum length in bytes of its strings, and the most steps drawing one takes
+#[derive(Clone, Debug)]
+struct Node {
+    kind:  Kind,
+    width: usize,
+    steps: usize,
+}
+
+
+/// The most steps drawing a string may take, which bounds the size of the automata
+const _MAX_STEPS: usize = 1 << 16;
+
+/// The most states of the nondeterministic automaton all the deterministic states hold together
+const _MAX_SUBSETS: usize = 1 << 20;
+
+/// The most bits all the counts of the deterministic states take together
+const _MAX_BITS: usize = 1 << 28;
+
+
+#[derive(Clone, Debug)]
+enum Kind {
+    /// The index of a set of characters of the parser
+    Set(usize),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat(Box<Node>, usize, usize),
+}
+
+
+/// A parser of regexes, which keeps the position of the next character and the distinct sets of characters
+struct Parser {
+    chars: Vec<char>,
+    pos:   usize,
+    sets:  Vec<Vec<char>>,
+    ids:   HashMap<Vec<char>, usize>,
+}
+
+
+/// A nondeterministic automaton of a regex, which has no cycle since every repetition is bounded
+///
+/// State `i` moves to the states of `eps[i]` without a character, and to the target of `edge[i]` on a character of its set.
+#[derive(Default)]
+struct Nfa {
+    eps:  Vec<Vec<usize>>,
+    edge: Vec<Option<(usize, usize)>>,
+}
+
+
+/// A deterministic automaton of a regex, the start is the state 0
+///
+/// The characters which are in the same sets lead to the same states, they are grouped together.
+#[derive(Clone, Debug)]
+struct Dfa {
+    groups: Vec<Vec<char>>,
+    states: Vec<State>,
+}
+
+
+/// A state of a `Dfa`, which moves on a character of a group to another state,
+/// with the number of strings which finish from it, the empty one included if it accepts
+#[derive(Clone, Debug)]
+struct State {
+    accept: bool,
+    next:   Vec<(usize, usize)>,
+    count:  BigUint,
+}
+
+
+impl RandRegex {
+    /// Parse a regex, see [`RandRegex`] for the syntax
+    #[inline]
+    pub fn new(regex: &str) -> Result<Self, GenError> {
+        let mut parser = Parser { chars: regex.chars().collect(), pos: 0, sets: vec![], ids: HashMap::new() };
+
+        parser.eat('^');
+        let root = parser.alt()?;
+        parser.eat('$');
+
+        if let Some(x) = parser.peek() {
+            return Err(parser.error(format!("unexpected `{}`", x)));
+        }
+        if root.steps > _MAX_STEPS {
+            return Err(GenError::InvalidRegex(format!("more than {} steps", _MAX_STEPS)));
+        }
+
+        let dfa = Dfa::new(&root, &parser.sets)?;
+
+        if dfa.states[0].count.is_zero() {
+            Err(GenError::InvalidRegex("no string matches".into()))
+        } else {
+            Ok(RandRegex { dfa, width: root.width, rng: RngSource::Thread })
+        }
+    }
+
+    /// Return the source of randomness used by `generate`
+    #[inline]
+    pub fn rng(&self) -> RngSource { self.rng }
+
+    /// Change the source of randomness used by `generate`
+    #[inline]
+    pub fn set_rng(&mut self, rng: RngSource) { self.rng = rng; }
+
+    /// Return the number of distinct matching strings and the entropy of `generate`
+    #[inline]
+    pub fn entropy(&self) -> Entropy { Entropy::uniform(self.dfa.states[0].count.clone()) }
+
+    /// Generate a matching string, drawing from the [`RngSource`] it carries
+    #[inline]
+    pub fn generate(&self) -> Result<Zeroizing<String>, GenError> {
+        self.rng.with_rng(|mut rng| self.generate_with(&mut rng))
+    }
+
+    /// Generate a matching string with the given random number generator
+    #[inline]
+    pub fn generate_with<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Result<Zeroizing<String>, GenError> {
+        let mut out = Zeroizing::new(String::new());
+        out.try_reserve_exact(self.width).map_err(|_| GenError::TooLarge(self.width.to_string()))?;
+        self.dfa.sample(rng, &mut out);
+
+        Ok(out)
+    }
+}
+
+
+impl Node {
+    #[inline]
+    fn concat(nodes: Vec<Node>) -> Self {
+        let width = nodes.iter().fold(0usize, |sum, x| sum.saturating_add(x.width));
+        let steps = nodes.iter().fold(1usize, |sum, x| sum.saturating_add(x.steps));
+        Node { kind: Kind::Concat(nodes), width, steps }
+    }
+
+    #[inline]
+    fn alt(nodes: Vec<Node>) -> Self {
+        let width = nodes.iter().map(|x| x.width).max().unwrap_or(0);
+        let steps = nodes.iter().map(|x| x.steps).max().unwrap_or(0).saturating_add(1);
+        Node { kind: Kind::Alt(nodes), width, steps }
+    }
+
+    /// The steps of repeating `node` up to `max` times
+    #[inline]
+    fn repeat_steps(node: &Node, max: usize) -> usize { node.steps.saturating_mul(max).saturating_add(1) }
+
+    /// Repeat `node` from `min` to `max` times, the steps must be within `_MAX_STEPS`
+    #[inline]
+    fn repeat(node: Node, min: usize, max: usize) -> Self {
+        let width = node.width.saturating_mul(max);
+        let steps = Node::repeat_steps(&node, max);
+        Node { kind: Kind::Repeat(Box::new(node), min, max), width, steps }
+    }
+}
+
+
+impl Nfa {
+    /// Add a state without any move
+    #[inline]
+    fn state(&mut self) -> usize {
+        self.eps.push(vec![]);
+        self.edge.push(None);
+        self.eps.len() - 1
+    }
+
+    /// Add the states of `node`, return the first and the last one
+    ///
+    /// A repetition is unrolled, every optional copy may be skipped straight to the last state.
+    #[inline]
+    fn build(&mut self, node: &Node) -> (usize, usize) {
+        let (first, last) = (self.state(), self.state());
+
+        match &node.kind {
+            Kind::Set(set) => self.edge[first] = Some((*set, last)),
+            Kind::Concat(nodes) => {
+                let end = nodes.iter().fold(first, |end, x| {
+                    let (a, b) = self.build(x);
+                    self.eps[end].push(a);
+                    b
+                });
+                self.eps[end].push(last);
+            }
+            Kind::Alt(nodes) => nodes.iter().for_each(|x| {
+                let (a, b) = self.build(x);
+                self.eps[first].push(a);
+                self.eps[b].push(last);
+            }),
+            Kind::Repeat(x, min, max) => {
+                let end = (0..*max).fold(first, |end, i| {
+                    if i >= *min {
+                        self.eps[end].push(last);
+                    }
+                    let (a, b) = self.build(x);
+                    self.eps[end].push(a);
+                    b
+                });
+                self.eps[end].push(last);
+            }
+        }
+
+        (first, last)
+    }
+
+    /// The states reached from `seeds` without a character, only the ones which move on a character, or `end`
+    #[inline]
+    fn closure(&self, mut stack: Vec<usize>, end: usize, seen: &mut [bool]) -> Vec<usize> {
+        let mut reached = vec![];
+
+        while let Some(x) = stack.pop() {
+            if !seen[x] {
+                seen[x] = true;
+                reached.push(x);
+                stack.extend(&self.eps[x]);
+            }
+        }
+
+        reached.iter().for_each(|x| seen[*x] = false);
+        reached.retain(|x| self.edge[*x].is_some() || *x == end);
+        reached.sort_unstable();
+
+        reached
+    }
+}
+
+
+impl Dfa {
+    /// Build the automaton of `root` by the subset construction, then count the strings from the last states back
+    #[inline]
+    #[rustfmt::skip]
+    fn new(root: &Node, sets: &[Vec<char>]) -> Result<Self, GenError> {
+
+        let mut nfa = Nfa::default();
+        let (start, end) = nfa.build(root);
+
+        // Group the characters by the sets they are in, in the order of the characters
+        let mut within: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+        sets.iter().enumerate().for_each(|(i, set)| set.iter().for_each(|x| within.entry(*x).or_default().push(i)));
+
+        let mut groups: Vec<Vec<char>> = vec![];
+        let mut by_sets: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut of_set = vec![vec![]; sets.len()];
+
+        for (x, ids) in within {
+            let g = *by_sets.entry(ids.clone()).or_insert_with(|| {
+                ids.iter().for_each(|i| of_set[*i].push(groups.len()));
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[g].push(x);
+        }
+
+        let too_large = |what: &str| GenError::InvalidRegex(format!("too {} to count its strings", what));
+
+        let mut seen = vec![false; nfa.eps.len()];
+        let first = nfa.closure(vec![start], end, &mut seen);
+        let mut size = first.len();
+        let mut index: HashMap<Vec<usize>, usize> = HashMap::new();
+        index.insert(first.clone(), 0);
+        let mut queue = VecDeque::from(vec![first]);
+        let mut states = vec![];
+
+        // The states are numbered in the order they are found
+        while let Some(subset) = queue.pop_front() {
+            let mut moves: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+            for (set, to) in subset.iter().filter_map(|x| nfa.edge[*x]) {
+                of_set[set].iter().for_each(|g| moves.entry(*g).or_default().push(to));
+            }
+
+            let mut next = Vec::with_capacity(moves.len());
+
+            for (g, seeds) in moves {
+                let target = nfa.closure(seeds, end, &mut seen);
+
+                let id = match index.get(&target) {
+                    Some(id) => *id,
+                    None => {
+                        size += target.len();
+                        if size > _MAX_SUBSETS {
+                            return Err(too_large("ambiguous"));
+                        }
+
+                        let id = index.len();
+                        index.insert(target.clone(), id);
+                        queue.push_back(target);
+                        id
+                    }
+                };
+
+                next.push((g, id));
+            }
+
+            states.push(State { accept: subset.binary_search(&end).is_ok(), next, count: BigUint::zero() });
+        }
+
+        // Every state is counted after the states it moves to, there's no cycle
+        let mut done = vec![false; states.len()];
+        let mut stack = vec![0];
+        let mut bits = 0u64;
+
+        while let Some(&i) = stack.last() {
+            let pending: Vec<usize> = states[i].next.iter().map(|x| x.1).filter(|x| !done[*x]).collect();
+
+            if !pending.is_empty() {
+                stack.extend(pending);
+                continue;
+            }
+
+            stack.pop();
+            if done[i] {
+                continue;
+            }
+
+            let count =
+                states[i].next
+                         .iter()
+                         .fold(BigUint::from(states[i].accept as u8), |sum, (g, to)| sum + &states[*to].count * groups[*g].len());
+
+            bits += count.bits();
+            if bits > _MAX_BITS as u64 {
+                return Err(too_large("long"));
+            }
+
+            states[i].count = count;
+            done[i] = true;
+        }
+
+        Ok(Dfa { groups, states })
+
+    }
+
+    /// Append a matching string drawn uniformly
+    ///
+    /// One number below the count of the start picks the string, it's split by the counts of the states on the way.
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R, out: &mut String) {
+        let mut state = &self.states[0];
+        let mut r = rng.gen_biguint_below(&state.count);
+
+        'walk: loop {
+            if state.accept {
+                if r.is_zero() {
+                    return;
+                }
+                r -= 1u8;
+            }
+
+            for (g, to) in &state.next {
+                let next = &self.states[*to];
+                let block = &next.count * self.groups[*g].len();
+
+                if r < block {
+                    out.push(self.groups[*g][(&r / &next.count).to_usize().unwrap_or_default()]);
+
+                    r %= &next.count;
+                    state = next;
+                    continue 'walk;
+                }
+                r -= block;
+            }
+
+            return;
+        }
+    }
+}
+
+
+impl Parser {
+    #[inline]
+    fn peek(&self) -> Option<char> { self.chars.get(self.pos).copied() }
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let x = self.peek();
+        self.pos += 1;
+        x
+    }
+
+    /// Skip `x` if it's the next character
+    #[inline]
+    fn eat(&mut self, x: char) -> bool {
+        let eaten = self.peek() == Some(x);
+        if eaten {
+            self.pos += 1;
+        }
+        eaten
+    }
+
+    #[inline]
+    fn error(&self, msg: impl AsRef<str>) -> GenError { GenError::InvalidRegex(format!("{} at {}", msg.as_ref(), self.pos)) }
+
+    /// Alternatives separated by `|`
+    #[inline]
+    fn alt(&mut self) -> Result<Node, GenError> {
+        let mut nodes = vec![self.concat()?];
+
+        while self.eat('|') {
+            nodes.push(self.concat()?);
+        }
+
+        Ok(if nodes.len() == 1 { nodes.remove(0) } else { Node::alt(nodes) })
+    }
+
+    /// Repeated atoms one after another
+    #[inline]
+    fn concat(&mut self) -> Result<Node, GenError> {
+        let mut nodes = vec![];
+
+        loop {
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                Some('$') if self.pos + 1 == self.chars.len() => break,
+                _ => {
+                    let atom = self.atom()?;
+                    nodes.push(self.repeat(atom)?);
+                }
+            }
+        }
+
+        Ok(if nodes.len() == 1 { nodes.remove(0) } else { Node::concat(nodes) })
+    }
+
+    /// The quantifiers after an atom
+    #[inline]
+    fn repeat(&mut self, mut node: Node) -> Result<Node, GenError> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('?') => {
+                    self.pos += 1;
+                    (0, 1)
+                }
+                Some('{') => self.bounds()?,
+                Some(x) if x == '*' || x == '+' => return Err(self.error(format!("unbounded `{}`, use `{{n,m}}`", x))),
+                _ => return Ok(node),
+            };
+
+            // A lazy repetition generates the same strings
+            self.eat('?');
+
+            if min > max {
+                return Err(self.error(format!("`{{{},{}}}` which is empty", min, max)));
+            }
+            if Node::repeat_steps(&node, max) > _MAX_STEPS {
+                return Err(self.error(format!("`{{{},{}}}` which takes more than {} steps", min, max, _MAX_STEPS)));
+            }
+
+            node = Node::repeat(node, min, max);
+        }
+    }
+
+    /// `{n}`, `{n,m}` or `{,m}`
+    #[inline]
+    fn bounds(&mut self) -> Result<(usize, usize), GenError> {
+        self.pos += 1;
+        let min = self.number()?;
+
+        let max = if self.eat(',') {
+            self.number()?.ok_or_else(|| self.error("unbounded `{n,}`, use `{n,m}`"))?
+        } else {
+            min.ok_or_else(|| self.error("empty repetition"))?
+        };
+
+        if self.eat('}') {
+            Ok((min.unwrap_or(0), max))
+        } else {
+            Err(self.error("unclosed repetition"))
+        }
+    }
+
+    /// The digits of a repetition
+    #[inline]
+    fn number(&mut self) -> Result<Option<usize>, GenError> {
+        let start = self.pos;
+
+        while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        match self.chars[start..self.pos].iter().collect::<String>() {
+            x if x.is_empty() => Ok(None),
+            x => x.parse().map(Some).map_err(|_| GenError::TooLarge(x)),
+        }
+    }
+
+    /// A character, a class, an escape or a group
+    #[inline]
+    fn atom(&mut self) -> Result<Node, GenError> {
+        let x = self.next().ok_or_else(|| self.error("unexpected end"))?;
+
+        match x {
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err(self.error("unsupported group `(?`"));
+                }
+
+                let node = self.alt()?;
+
+                if self.eat(')') {
+                    Ok(node)
+                } else {
+                    Err(self.error("unclosed group"))
+                }
+            }
+            '[' => self.class(),
+            '.' => Ok(self.set(_PRINTABLE().collect())),
+            '\\' => {
+                let set = self.escape()?;
+                Ok(self.set(set))
+            }
+            '?' | '*' | '+' | '{' => Err(self.error(format!("nothing to repeat by `{}`", x))),
+            '^' | '$' => Err(self.error(format!("unsupported anchor `{}`", x))),
+            x if x.is_control() => Err(self.error(format!("control character {:?}", x))),
+            x => Ok(self.set(Some(x).into_iter().collect())),
+        }
+    }
+
+    /// The characters of an escape after `\`
+    #[inline]
+    fn escape(&mut self) -> Result<BTreeSet<char>, GenError> {
+        let x = self.next().ok_or_else(|| self.error("unexpected end"))?;
+
+        let digit = |x: &char| x.is_ascii_digit();
+        let word = |x: &char| x.is_ascii_alphanumeric() || *x == '_';
+        let space = |x: &char| *x == ' ';
+
+        let set = match x {
+            'd' => _PRINTABLE().filter(digit).collect(),
+            'D' => _PRINTABLE().filter(|x| !digit(x)).collect(),
+            'w' => _PRINTABLE().filter(word).collect(),
+            'W' => _PRINTABLE().filter(|x| !word(x)).collect(),
+            's' => _PRINTABLE().filter(space).collect(),
+            'S' => _PRINTABLE().filter(|x| !space(x)).collect(),
+            x if x.is_ascii_punctuation() || x == ' ' => Some(x).into_iter().collect(),
+            x => return Err(self.error(format!("unsupported escape `\\{}`", x))),
+        };
+
+        Ok(set)
+    }
+
+    /// A class after `[`
+    #[inline]
+    fn class(&mut self) -> Result<Node, GenError> {
+        let negated = self.eat('^');
+        let mut set = BTreeSet::new();
+        let mut first = true;
+
+        loop {
+            let x = self.next().ok_or_else(|| self.error("unclosed class"))?;
+
+            let lo = match x {
+                ']' if !first => break,
+                '[' if self.peek() == Some(':') => return Err(self.error("unsupported class `[:`")),
+                '\\' => {
+                    let escaped = self.escape()?;
+                    if escaped.len() != 1 {
+                        set.extend(escaped);
+                        first = false;
+                        continue;
+                    }
+                    escaped.into_iter().next().unwrap_or(x)
+                }
+                x if x.is_control() => return Err(self.error(format!("control character {:?}", x))),
+                x => x,
+            };
+            first = false;
+
+            // A range, unless the `-` is the last character of the class
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|x| *x != ']') {
+                self.pos += 1;
+                let hi = match self.next() {
+                    Some('\\') => {
+                        let escaped = self.escape()?;
+                        match escaped.len() {
+                            1 => escaped.into_iter().next().unwrap_or(lo),
+                            _ => return Err(self.error("a class can't end a range")),
+                        }
+                    }
+                    Some(x) => x,
+                    None => return Err(self.error("unclosed class")),
+                };
+
+                if lo > hi {
+                    return Err(self.error(format!("`{}-{}` which is empty", lo, hi)));
+                }
+                set.extend((lo..=hi).filter(|x| !x.is_control()));
+            } else {
+                set.insert(lo);
+            }
+        }
+
+        if negated {
+            set = _PRINTABLE().filter(|x| !set.contains(x)).collect();
+        }
+
+        Ok(self.set(set))
+    }
+
+    /// A node of `set`, the same sets share one index
+    #[inline]
+    fn set(&mut self, set: BTreeSet<char>) -> Node {
+        let width = set.iter().map(|x| x.len_utf8()).max().unwrap_or(0);
+        let chars: Vec<char> = set.into_iter().collect();
+
+        let sets = &mut self.sets;
+        let id = *self.ids.entry(chars).or_insert_with_key(|x| {
+            sets.push(x.clone());
+            sets.len() - 1
+        });
+
+        Node { kind: Kind::Set(id), width, steps: 1 }
+    }
+}
+
+
+/// The printable ASCII characters, from the space to `~`
+#[inline]
+fn _PRINTABLE() -> impl Iterator<Item = char> { (32..127u8).map(char::from) }
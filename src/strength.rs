@@ -0,0 +1,792 @@
+use {
+    crate::{error::GenError, utils::{_CNT, _DEFAULT_DATA}},
+    unicode_segmentation::UnicodeSegmentation,
+    std::{
+        cmp::Reverse,
+        collections::{BTreeMap, HashMap},
+        fs,
+        ops::Range,
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+#[cfg(feature = "bundled-dictionaries")]
+use std::sync::OnceLock;
+
+
+
+
+/// The least guesses of a match of one character, and of more, which is not the whole password
+const _MIN_GUESSES_SINGLE: f64 = 10.0;
+const _MIN_GUESSES_MULTI: f64 = 50.0;
+
+/// The years a date is assumed to be away from now at least
+const _MIN_YEAR_SPACE: f64 = 20.0;
+
+/// The characters of a password which are estimated, the rest only make it stronger
+const _MAX_LEN: usize = 100;
+
+/// The size of the pool of the characters beyond the default classes
+const _OTHER_POOL: usize = 100;
+
+/// The guesses below which a password gets the score 0, 1, 2 and 3
+const _SCORES: [f64; 4] = [1e3 + 5.0, 1e6 + 5.0, 1e8 + 5.0, 1e10 + 5.0];
+
+/// The rows of a US keyboard, unshifted and shifted, and how far each row is shifted to the right in keys
+const _KEYBOARD: [(&str, &str, f64); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+", 0.0),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|", 1.5),
+    ("asdfghjkl;'", "ASDFGHJKL:\"", 1.75),
+    ("zxcvbnm,./", "ZXCVBNM<>?", 2.25),
+];
+
+/// The letters the l33t characters stand for
+const _L33T: &[(&str, &[&str])] = &[
+    ("4", &["a"]), ("@", &["a"]), ("8", &["b"]), ("(", &["c"]), ("{", &["c"]), ("[", &["c"]), ("<", &["c"]),
+    ("3", &["e"]), ("6", &["g"]), ("9", &["g"]), ("1", &["i", "l"]), ("!", &["i"]), ("|", &["i", "l"]),
+    ("7", &["l", "t"]), ("0", &["o"]), ("$", &["s"]), ("5", &["s"]), ("+", &["t"]), ("%", &["x"]), ("2", &["z"]),
+];
+
+
+/// Words in lower case and their ranks, the most common one is 1, and the characters of the longest word
+type Ranked = (HashMap<String, usize>, usize);
+
+/// For the characters up to some position, the best sequence of every number of matches,
+/// with its guesses, the product of the guesses of its matches, and its last match
+type Optimal = BTreeMap<usize, (f64, f64, Match)>;
+
+
+
+
+/// How many guesses it takes to crack a password a user picked, in the way of [zxcvbn](https://github.com/dropbox/zxcvbn)
+///
+/// The password is split into the cheapest sequence of matches: common passwords, English words and names,
+/// also reversed or in l33t, keyboard walks on a US keyboard, repeats, sequences like `abc` or `9753`, years and dates.
+/// The characters no pattern matches are brute forced, from the pool of the classes of [`RandKey`](crate::RandKey)
+/// which the password touches, counted by the same rules as `to_randkey`.
+///
+/// A strength is estimated through [`Dictionaries`], with the word lists it's given: load lists of zxcvbn's size,
+/// tens of thousands of words each, since the estimate errs on the high side for words beyond them.
+/// Pass the user name, the email or the like to [`Dictionaries::estimate_with`] as well.
+///
+/// The `bundled-dictionaries` feature adds [`Strength::estimate`] and [`Dictionaries::bundled`], whose lists are small:
+/// a few hundred of the most common passwords, English words and names, and the English words of BIP-39.
+/// They're meant for tests and demos, not for scoring the passwords of users.
+///
+/// Only the first 100 characters are estimated, which keeps the time bounded whatever the password is,
+/// the characters after them would only add guesses. It keeps no part of the password, the matches only have ranges.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{Dictionaries, Dictionary, Pattern};
+///
+/// let mut dicts = Dictionaries::new();
+/// dicts.set(Dictionary::Passwords, &["123456", "password", "12345678"]);
+///
+/// let strength = dicts.estimate("P@ssw0rd");
+/// assert_eq!(strength.score(), 0);
+/// assert!(matches!(strength.sequence()[0].pattern(), Pattern::Dictionary { l33t: true, .. }));
+///
+/// let strength = dicts.estimate_with("alice_1990", &["alice", "alice@example.com"]);
+/// assert!(matches!(strength.sequence()[0].pattern(), Pattern::Dictionary { dictionary: Dictionary::UserInputs, .. }));
+/// assert!(matches!(strength.sequence()[2].pattern(), Pattern::Year));
+///
+/// let strength = dicts.estimate("correct-horse-battery-staple");
+/// assert_eq!(strength.score(), 4);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strength {
+    guesses:  f64,
+    sequence: Vec<Match>,
+}
+
+
+/// A part of a password which follows a pattern, see [`Strength`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pattern: Pattern,
+    range:   Range<usize>,
+    guesses: f64,
+}
+
+
+/// The patterns of [`Match`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// A word of a dictionary, `rank` 1 is the most common one
+    Dictionary { dictionary: Dictionary, rank: usize, l33t: bool, reversed: bool },
+    /// Adjacent keys, `turns` is the number of straight runs and `shifted` the number of characters typed with shift
+    Spatial { turns: usize, shifted: usize },
+    /// A part repeated `times` times
+    Repeat { times: usize },
+    /// Characters whose code points step by the same amount, like `abc`, `ZYX` or `2468`
+    Sequence { ascending: bool },
+    /// A day, month and year in some order, like `25121990` or `1990-12-25`
+    Date { separator: bool },
+    /// A year from 1900 to 2050
+    Year,
+    /// None of the above
+    Bruteforce,
+}
+
+
+/// The ranked word lists a [`Strength`] is estimated with, one list for every kind of [`Dictionary`]
+///
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{Dictionaries, Dictionary, Pattern};
+///
+/// // A frequency list, the most common word first, the counts after the words are dropped
+/// let mut dicts = Dictionaries::new();
+/// dicts.set(Dictionary::Passwords, &["zebraquorra 3120", "123456 2900"]);
+/// assert_eq!(dicts.size(Dictionary::Passwords), 2);
+///
+/// let strength = dicts.estimate("ZebraQuorra");
+/// assert!(matches!(strength.sequence()[0].pattern(), Pattern::Dictionary { dictionary: Dictionary::Passwords, rank: 1, .. }));
+/// assert_eq!(strength.score(), 0);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Dictionaries {
+    lists: Vec<(Dictionary, Ranked)>,
+}
+
+
+/// The dictionaries of [`Pattern::Dictionary`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Dictionary {
+    /// The most common passwords of leaks
+    Passwords,
+    /// Common English words
+    English,
+    /// Common first names and last names
+    Names,
+    /// The words passed to [`Dictionaries::estimate_with`]
+    UserInputs,
+}
+
+
+impl Strength {
+    /// Estimate the strength of `password` with the bundled dictionaries, see [`Dictionaries::bundled`]
+    ///
+    /// # Example
+    ///
+    /// Basic usage:
+    /// ```
+    /// use rand_key::Strength;
+    ///
+    /// let strength = Strength::estimate("correct-horse-battery-staple");
+    /// assert_eq!(strength.score(), 4);
+    /// println!("{:.1e} guesses", strength.guesses());
+    /// // 1.1e36 guesses
+    /// ```
+    #[cfg(feature = "bundled-dictionaries")]
+    #[inline]
+    pub fn estimate(password: &str) -> Self {
+        let none: &[&str] = &[];
+        Self::estimate_with(password, none)
+    }
+
+    /// Estimate the strength of `password` with the bundled dictionaries and `user_inputs`,
+    /// see [`Dictionaries::estimate_with`]
+    #[cfg(feature = "bundled-dictionaries")]
+    #[inline]
+    pub fn estimate_with(password: &str, user_inputs: &[impl AsRef<str>]) -> Self { _BUILTIN().estimate_with(password, user_inputs) }
+
+    /// Return the number of guesses it takes to crack the password
+    #[inline]
+    pub fn guesses(&self) -> f64 { self.guesses }
+
+    /// Return the base 10 logarithm of the guesses
+    #[inline]
+    pub fn guesses_log10(&self) -> f64 { self.guesses.log10() }
+
+    /// Return the score from 0 to 4, the thresholds are 10^3, 10^6, 10^8 and 10^10 guesses like zxcvbn
+    #[inline]
+    pub fn score(&self) -> u8 { _SCORES.iter().filter(|x| self.guesses >= **x).count() as u8 }
+
+    /// Return the matches the password is split into, in order
+    #[inline]
+    pub fn sequence(&self) -> &[Match] { &self.sequence }
+}
+
+
+impl Dictionaries {
+    /// Return the dictionaries without any word
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Return the bundled dictionaries, which [`Strength::estimate`] uses
+    ///
+    /// They only hold a few hundred words each, see [`Strength`]. Only with the `bundled-dictionaries` feature.
+    #[cfg(feature = "bundled-dictionaries")]
+    #[inline]
+    pub fn bundled() -> Self { _BUILTIN().clone() }
+
+    /// Replace the list of `dictionary` with `words`, the most common one first
+    ///
+    /// Like in a frequency list, anything after the first whitespace of a word is dropped. Blank words are skipped.
+    #[inline]
+    pub fn set(&mut self, dictionary: Dictionary, words: &[impl AsRef<str>]) {
+        let ranked = _RANKED(words.iter().filter_map(|x| x.as_ref().split_whitespace().next()));
+
+        match self.lists.iter_mut().find(|(x, _)| *x == dictionary) {
+            Some(list) => list.1 = ranked,
+            None => self.lists.push((dictionary, ranked)),
+        }
+    }
+
+    /// Replace the list of `dictionary` with the words of a file, one per line, see [`Dictionaries::set`]
+    #[inline]
+    pub fn set_from_file(&mut self, dictionary: Dictionary, path: impl AsRef<Path>) -> Result<(), GenError> {
+        let text = fs::read_to_string(path)?;
+        self.set(dictionary, &text.lines().collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    /// Return the number of distinct words of `dictionary`
+    #[inline]
+    pub fn size(&self, dictionary: Dictionary) -> usize {
+        self.lists.iter().find(|(x, _)| *x == dictionary).map_or(0, |(_, (ranked, _))| ranked.len())
+    }
+
+    /// Estimate the strength of `password` with these dictionaries
+    #[inline]
+    pub fn estimate(&self, password: &str) -> Strength {
+        let none: &[&str] = &[];
+        self.estimate_with(password, none)
+    }
+
+    /// Estimate the strength of `password` with these dictionaries, which is weaker if it contains some of `user_inputs`
+    ///
+    /// The inputs are ranked in their order, like the name of the user, the email and the name of the site.
+    #[inline]
+    pub fn estimate_with(&self, password: &str, user_inputs: &[impl AsRef<str>]) -> Strength {
+        let inputs = _RANKED(user_inputs.iter().map(AsRef::as_ref));
+
+        let mut dicts: Vec<(Dictionary, &Ranked)> = self.lists.iter().map(|(x, words)| (*x, words)).collect();
+        dicts.push((Dictionary::UserInputs, &inputs));
+
+        _ESTIMATE(&password.graphemes(true).take(_MAX_LEN).collect::<Vec<_>>(), &dicts)
+    }
+}
+
+
+impl Match {
+    /// Return the pattern of the match
+    #[inline]
+    pub fn pattern(&self) -> &Pattern { &self.pattern }
+
+    /// Return the range of the match in the characters of the password, which are grapheme clusters
+    #[inline]
+    pub fn range(&self) -> Range<usize> { self.range.clone() }
+
+    /// Return the number of guesses it takes to crack the match alone
+    #[inline]
+    pub fn guesses(&self) -> f64 { self.guesses }
+}
+
+
+
+
+/// Rank the words in their order, the later duplicates are skipped
+#[inline]
+fn _RANKED<'a>(words: impl Iterator<Item = &'a str>) -> Ranked {
+    let mut ranked = HashMap::new();
+    let mut longest = 0;
+
+    for word in words.map(str::to_lowercase).filter(|x| !x.is_empty()) {
+        let rank = ranked.len() + 1;
+        longest = longest.max(word.graphemes(true).count());
+        ranked.entry(word).or_insert(rank);
+    }
+
+    (ranked, longest)
+}
+
+
+/// The bundled dictionaries, ranked once
+#[cfg(feature = "bundled-dictionaries")]
+#[inline]
+fn _BUILTIN() -> &'static Dictionaries {
+    static BUILTIN: OnceLock<Dictionaries> = OnceLock::new();
+
+    BUILTIN.get_or_init(|| {
+        let english = include_str!("../wordlists/english.txt").lines().chain(include_str!("../wordlists/bip39_english.txt").lines());
+
+        Dictionaries {
+            lists: vec![(Dictionary::Passwords, _RANKED(include_str!("../wordlists/passwords.txt").lines())),
+                        (Dictionary::English,   _RANKED(english)),
+                        (Dictionary::Names,     _RANKED(include_str!("../wordlists/names.txt").lines())),],
+        }
+    })
+}
+
+
+#[inline]
+fn _ESTIMATE(items: &[&str], dicts: &[(Dictionary, &Ranked)]) -> Strength {
+    let mut matches = _DICTIONARY_MATCHES(items, dicts);
+    matches.extend(_SPATIAL_MATCHES(items));
+    matches.extend(_REPEAT_MATCHES(items, dicts));
+    matches.extend(_SEQUENCE_MATCHES(items));
+    matches.extend(_DATE_MATCHES(items));
+
+    _MOST_GUESSABLE(items, matches)
+}
+
+
+/// Return the only character of `item`
+#[inline]
+fn _CHAR(item: &str) -> Option<char> {
+    let mut chars = item.chars();
+    chars.next().filter(|_| chars.next().is_none())
+}
+
+
+/// The binomial coefficient
+#[inline]
+fn _NCK(n: usize, k: usize) -> f64 { (1..=k).fold(1.0, |x, i| x * (n + 1 - i) as f64 / i as f64) }
+
+
+/// The ways to pick the positions of `a` characters of one kind and `b` of another, where at least one of each is kept
+#[inline]
+fn _VARIATIONS(a: usize, b: usize) -> f64 { (1..=a.min(b)).map(|i| _NCK(a + b, i)).sum() }
+
+
+/// Every part of `words` which is a word of some dictionary, with its dictionary and rank
+#[inline]
+fn _LOOKUP(words: &[String], dicts: &[(Dictionary, &Ranked)]) -> Vec<(Range<usize>, Dictionary, usize)> {
+    let mut found = vec![];
+
+    for (dictionary, (ranked, longest)) in dicts {
+        for i in 0..words.len() {
+            for j in i + 1..=words.len().min(i + longest) {
+                if let Some(rank) = ranked.get(&words[i..j].concat()) {
+                    found.push((i..j, *dictionary, *rank));
+                }
+            }
+        }
+    }
+
+    found
+}
+
+
+/// The ways to capitalize a word, like `Password`, `PASSWORD` or `pAssWord`
+#[inline]
+fn _UPPERCASE_VARIATIONS(token: &[&str]) -> f64 {
+    let is_upper = |x: &str| x.chars().any(char::is_uppercase);
+    let upper = token.iter().filter(|x| is_upper(x)).count();
+    let lower = token.iter().filter(|x| x.chars().any(char::is_lowercase)).count();
+
+    if upper == 0 {
+        1.0
+    } else if lower == 0 || upper == 1 && (token.first().is_some_and(|x| is_upper(x)) || token.last().is_some_and(|x| is_upper(x))) {
+        2.0
+    } else {
+        _VARIATIONS(upper, lower)
+    }
+}
+
+
+/// Words of the dictionaries, as they are, reversed or in l33t
+#[inline]
+#[rustfmt::skip]
+fn _DICTIONARY_MATCHES(items: &[&str], dicts: &[(Dictionary, &Ranked)]) -> Vec<Match> {
+
+    let n = items.len();
+    let lower: Vec<String> = items.iter().map(|x| x.to_lowercase()).collect();
+    let mut matches = vec![];
+
+    let word = |range: Range<usize>, dictionary, rank, l33t, reversed, variations: f64| {
+        let guesses = rank as f64 * _UPPERCASE_VARIATIONS(&items[range.clone()]) * variations * if reversed { 2.0 } else { 1.0 };
+        Match { pattern: Pattern::Dictionary { dictionary, rank, l33t, reversed }, range, guesses }
+    };
+
+    for (range, dictionary, rank) in _LOOKUP(&lower, dicts) {
+        matches.push(word(range, dictionary, rank, false, false, 1.0));
+    }
+
+    let reversed: Vec<String> = lower.iter().rev().cloned().collect();
+
+    for (range, dictionary, rank) in _LOOKUP(&reversed, dicts) {
+        // A palindrome is cheaper as it is
+        if range.len() > 1 && reversed[range.clone()] != lower[range.clone()] {
+            matches.push(word(n - range.end..n - range.start, dictionary, rank, false, true, 1.0));
+        }
+    }
+
+    // Every l33t character stands for one letter in the whole password
+    let mut subs: Vec<Vec<(&str, &str)>> = vec![vec![]];
+
+    for (l33t, letters) in _L33T.iter().filter(|(x, _)| lower.iter().any(|y| y == x)) {
+        subs = subs.iter().flat_map(|x| letters.iter().map(move |y| [x.as_slice(), &[(*l33t, *y)]].concat())).collect();
+    }
+
+    for sub in subs.iter().filter(|x| !x.is_empty()) {
+        let plain: Vec<String> = lower.iter()
+                                      .map(|x| sub.iter().find(|(y, _)| x == y).map_or_else(|| x.clone(), |(_, y)| y.to_string()))
+                                      .collect();
+
+        for (range, dictionary, rank) in _LOOKUP(&plain, dicts) {
+            let token = &lower[range.clone()];
+            let used: Vec<_> = sub.iter().filter(|(x, _)| token.iter().any(|y| y == x)).collect();
+
+            if range.len() > 1 && !used.is_empty() {
+                let variations = used.iter()
+                                     .map(|(x, y)| {
+                                         let cnt = |z: &str| token.iter().filter(|w| *w == z).count();
+                                         match (cnt(x), cnt(y)) { (_, 0) => 2.0, (s, u) => _VARIATIONS(s, u) }
+                                     })
+                                     .product();
+
+                matches.push(word(range, dictionary, rank, true, false, variations));
+            }
+        }
+    }
+
+    matches
+
+}
+
+
+/// The row, the horizontal position and whether it's shifted of the key of `item`
+#[inline]
+fn _KEY(item: &str) -> Option<(usize, f64, bool)> {
+    let ch = _CHAR(item)?;
+
+    _KEYBOARD.iter().enumerate().find_map(|(row, (plain, shifted, offset))| {
+        plain.chars()
+             .position(|x| x == ch)
+             .map(|col| (row, col as f64 + offset, false))
+             .or_else(|| shifted.chars().position(|x| x == ch).map(|col| (row, col as f64 + offset, true)))
+    })
+}
+
+
+/// The direction from key `a` to key `b`, if they are adjacent
+#[inline]
+fn _DIRECTION(a: (usize, f64), b: (usize, f64)) -> Option<(isize, bool)> {
+    let (rows, dx) = (b.0 as isize - a.0 as isize, b.1 - a.1);
+
+    match rows {
+        0 if (dx.abs() - 1.0).abs() < 1e-9 => Some((0, dx > 0.0)),
+        -1 | 1 if dx.abs() <= 1.0 => Some((rows, dx > 0.0)),
+        _ => None,
+    }
+}
+
+
+/// Runs of adjacent keys, at least 3 of them
+#[inline]
+#[rustfmt::skip]
+fn _SPATIAL_MATCHES(items: &[&str]) -> Vec<Match> {
+
+    let keys: Vec<_> = items.iter().map(|x| _KEY(x)).collect();
+    let all: Vec<_> = _KEYBOARD.iter().enumerate().flat_map(|(row, (plain, _, offset))| (0..plain.chars().count()).map(move |col| (row, col as f64 + offset))).collect();
+
+    // The average number of the neighbours of a key
+    let degree = all.iter().map(|a| all.iter().filter(|b| _DIRECTION(*a, **b).is_some()).count()).sum::<usize>() as f64 / all.len() as f64;
+
+    let mut matches = vec![];
+    let mut i = 0;
+
+    while i < items.len() {
+        let (mut j, mut turns, mut last) = (i + 1, 0, None);
+
+        while let (Some(Some(a)), Some(Some(b))) = (keys.get(j - 1), keys.get(j)) {
+            match _DIRECTION((a.0, a.1), (b.0, b.1)) {
+                Some(x) => {
+                    if last != Some(x) {
+                        turns += 1;
+                        last = Some(x);
+                    }
+                    j += 1;
+                }
+                None => break,
+            }
+        }
+
+        let len = j - i;
+
+        if len >= 3 {
+            let shifted = keys[i..j].iter().flatten().filter(|x| x.2).count();
+
+            let mut guesses: f64 = (2..=len).flat_map(|x| (1..=turns.min(x - 1)).map(move |y| (x, y)))
+                                            .map(|(x, y)| _NCK(x - 1, y - 1) * all.len() as f64 * degree.powi(y as i32))
+                                            .sum();
+            if shifted == len {
+                guesses *= 2.0;
+            } else if shifted > 0 {
+                guesses *= _VARIATIONS(shifted, len - shifted);
+            }
+
+            matches.push(Match { pattern: Pattern::Spatial { turns, shifted }, range: i..j, guesses });
+        }
+
+        i = j;
+    }
+
+    matches
+
+}
+
+
+/// Parts repeated at least twice, the base is estimated on its own
+#[inline]
+fn _REPEAT_MATCHES(items: &[&str], dicts: &[(Dictionary, &Ranked)]) -> Vec<Match> {
+    let n = items.len();
+    let mut matches = vec![];
+    let mut i = 0;
+
+    while i < n {
+        // The longest repeat, of the shortest base
+        let best = (1..=(n - i) / 2)
+            .map(|size| {
+                let base = &items[i..i + size];
+                (size, 1 + (1..).take_while(|k| items.get(i + k * size..i + (k + 1) * size) == Some(base)).count())
+            })
+            .filter(|(_, times)| *times > 1)
+            .max_by_key(|(size, times)| (size * times, Reverse(*size)));
+
+        match best {
+            Some((size, times)) => {
+                let base = _ESTIMATE(&items[i..i + size], dicts).guesses;
+                matches.push(Match { pattern: Pattern::Repeat { times }, range: i..i + size * times, guesses: base * times as f64 });
+                i += size * times;
+            }
+            None => i += 1,
+        }
+    }
+
+    matches
+}
+
+
+/// Runs of at least 3 characters of one kind, whose code points step by the same amount up to 5
+#[inline]
+#[rustfmt::skip]
+fn _SEQUENCE_MATCHES(items: &[&str]) -> Vec<Match> {
+
+    let kind = |x: char| if x.is_ascii_lowercase() { 0 } else if x.is_ascii_uppercase() { 1 } else if x.is_ascii_digit() { 2 } else { 3 };
+    let chars: Vec<Option<char>> = items.iter().map(|x| _CHAR(x)).collect();
+
+    let step = |i: usize| match (chars[i - 1], chars[i]) {
+        (Some(a), Some(b)) if kind(a) == kind(b) => Some(b as i64 - a as i64).filter(|x| (1..=5).contains(&x.abs())),
+        _ => None,
+    };
+
+    let mut matches = vec![];
+    let mut i = 0;
+
+    while i + 2 < items.len() {
+        let delta = step(i + 1);
+        let j = (i + 1..items.len()).find(|x| delta.is_none() || step(*x) != delta).unwrap_or(items.len());
+
+        if j - i >= 3 {
+            let ascending = delta.is_some_and(|x| x > 0);
+            let base = match items[i] {
+                "a" | "A" | "z" | "Z" | "0" | "1" | "9" => 4.0,
+                _ if chars[i].is_some_and(|y| y.is_ascii_digit()) => 10.0,
+                _ => 26.0,
+            };
+            let guesses = base * if ascending { 1.0 } else { 2.0 } * (j - i) as f64;
+
+            matches.push(Match { pattern: Pattern::Sequence { ascending }, range: i..j, guesses });
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+
+    matches
+
+}
+
+
+/// The current year
+#[inline]
+fn _THIS_YEAR() -> i64 { 1970 + (SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / 31_556_952) as i64 }
+
+
+/// The guesses of a year, or of the day of a year
+#[inline]
+fn _YEAR_GUESSES(year: i64) -> f64 { ((year - _THIS_YEAR()).abs() as f64).max(_MIN_YEAR_SPACE) }
+
+
+/// The year of a date of 3 parts, day and month in either order after or before the year,
+/// if there are several ways, the one closest to now
+#[inline]
+#[rustfmt::skip]
+fn _DATE_YEAR(parts: [&str; 3]) -> Option<i64> {
+
+    let num = |x: &str| x.parse::<i64>().ok();
+    let year = |x: &str| match (x.len(), num(x)?) {
+        (2, y) => Some(if y > 50 { 1900 + y } else { 2000 + y }),
+        (4, y) if (1000..=2050).contains(&y) => Some(y),
+        _ => None,
+    };
+    let day_month = |a: &str, b: &str| {
+        let (a, b) = (num(a).filter(|_| a.len() <= 2)?, num(b).filter(|_| b.len() <= 2)?);
+        Some(()).filter(|_| (1..=31).contains(&a) && (1..=12).contains(&b) || (1..=12).contains(&a) && (1..=31).contains(&b))
+    };
+
+    let this_year = _THIS_YEAR();
+
+    [(parts[0], parts[1], parts[2]), (parts[2], parts[0], parts[1])]
+        .iter()
+        .filter_map(|(y, a, b)| day_month(a, b).and_then(|_| year(y)))
+        .min_by_key(|y| (y - this_year).abs())
+
+}
+
+
+/// Years from 1900 to 2050, and dates with or without separators
+#[inline]
+#[rustfmt::skip]
+fn _DATE_MATCHES(items: &[&str]) -> Vec<Match> {
+
+    let n = items.len();
+    let digits = |range: Range<usize>| items[range].iter().all(|x| _CHAR(x).is_some_and(|y| y.is_ascii_digit()));
+    let mut matches = vec![];
+
+    for i in 0..n {
+        if i + 4 <= n && digits(i..i + 4) {
+            let year: i64 = items[i..i + 4].concat().parse().unwrap_or_default();
+
+            if (1900..=2050).contains(&year) {
+                matches.push(Match { pattern: Pattern::Year, range: i..i + 4, guesses: _YEAR_GUESSES(year) });
+            }
+        }
+
+        for j in (i + 4..=n.min(i + 8)).filter(|j| digits(i..*j)) {
+            let token = items[i..j].concat();
+
+            let year = (1..j - i - 1).flat_map(|a| (a + 1..j - i).map(move |b| (a, b)))
+                                     .filter_map(|(a, b)| _DATE_YEAR([&token[..a], &token[a..b], &token[b..]]))
+                                     .min_by_key(|y| (y - _THIS_YEAR()).abs());
+
+            if let Some(year) = year {
+                matches.push(Match { pattern: Pattern::Date { separator: false }, range: i..j, guesses: _YEAR_GUESSES(year) * 365.0 });
+            }
+        }
+
+        for j in i + 6..=n.min(i + 10) {
+            let token = &items[i..j];
+            let seps: Vec<usize> = (0..token.len()).filter(|x| [" ", "/", "\\", "_", ".", "-"].contains(&token[*x])).collect();
+
+            if let [a, b] = seps[..] {
+                let parts = [&token[..a], &token[a + 1..b], &token[b + 1..]];
+
+                if token[a] == token[b] && digits(i..i + a) && digits(i + a + 1..i + b) && digits(i + b + 1..j) {
+                    if let Some(year) = _DATE_YEAR([&parts[0].concat(), &parts[1].concat(), &parts[2].concat()]) {
+                        matches.push(Match { pattern: Pattern::Date { separator: true }, range: i..j, guesses: _YEAR_GUESSES(year) * 365.0 * 4.0 });
+                    }
+                }
+            }
+        }
+    }
+
+    matches
+
+}
+
+
+/// The size of the pool brute force picks from: the default classes the password touches,
+/// and a pool of `_OTHER_POOL` for the characters beyond them
+#[inline]
+fn _POOL(items: &[&str]) -> f64 {
+    let classes = _DEFAULT_DATA();
+    let (known, other): (Vec<&str>, Vec<&str>) = items.iter().copied().partition(|x| classes.iter().any(|class| class.data.iter().any(|y| y == *x)));
+
+    // Every character left is in some class
+    let cnts = _CNT(known.concat(), &classes).unwrap_or_default();
+    let pool: usize = classes.iter().zip(cnts).filter(|(_, cnt)| *cnt > 0).map(|(class, _)| class.data.len()).sum();
+
+    (pool + if other.is_empty() { 0 } else { _OTHER_POOL }) as f64
+}
+
+
+/// Keep the sequence of `l` matches ending with `m`, unless a sequence of fewer matches is as cheap
+#[inline]
+fn _UPDATE(optimal: &mut [Optimal], m: Match, l: usize) {
+    let k = m.range.end - 1;
+    let pi = m.guesses * if l > 1 { optimal[m.range.start - 1][&(l - 1)].1 } else { 1.0 };
+    let g = (1..=l).map(|x| x as f64).product::<f64>() * pi + 10000f64.powi(l as i32 - 1);
+
+    if !optimal[k].iter().any(|(x, (y, ..))| *x <= l && *y <= g) {
+        optimal[k].insert(l, (g, pi, m));
+    }
+}
+
+
+/// Pick the sequence of matches with the least guesses, the characters between the matches are brute forced
+///
+/// A sequence of `l` matches takes `l! * ∏ guesses + 10000^(l - 1)` guesses,
+/// which is the dynamic programming of zxcvbn.
+#[inline]
+#[rustfmt::skip]
+fn _MOST_GUESSABLE(items: &[&str], matches: Vec<Match>) -> Strength {
+
+    let n = items.len();
+
+    if n == 0 {
+        return Strength { guesses: 1.0, sequence: vec![] };
+    }
+
+    let pool = _POOL(items);
+    let min = |len: usize| if len == 1 { _MIN_GUESSES_SINGLE } else { _MIN_GUESSES_MULTI };
+    let bruteforce = |range: Range<usize>| Match { pattern: Pattern::Bruteforce, guesses: pool.powi(range.len() as i32).max(min(range.len()) + 1.0), range };
+
+    let mut ends: Vec<Vec<Match>> = vec![vec![]; n];
+
+    for mut m in matches {
+        if m.range.len() < n {
+            m.guesses = m.guesses.max(min(m.range.len()));
+        }
+        ends[m.range.end - 1].push(m);
+    }
+
+    let mut optimal: Vec<Optimal> = vec![BTreeMap::new(); n];
+
+    for (k, ms) in ends.into_iter().enumerate() {
+        for m in ms {
+            match m.range.start {
+                0 => _UPDATE(&mut optimal, m, 1),
+                i => for l in optimal[i - 1].keys().copied().collect::<Vec<_>>() {
+                    _UPDATE(&mut optimal, m.clone(), l + 1);
+                },
+            }
+        }
+
+        _UPDATE(&mut optimal, bruteforce(0..k + 1), 1);
+
+        // Brute force never follows brute force
+        for i in 1..=k {
+            let ls: Vec<usize> = optimal[i - 1].iter().filter(|(_, (.., m))| m.pattern != Pattern::Bruteforce).map(|(l, _)| *l).collect();
+
+            for l in ls {
+                _UPDATE(&mut optimal, bruteforce(i..k + 1), l + 1);
+            }
+        }
+    }
+
+    let (mut l, guesses) = optimal[n - 1].iter().map(|(l, (g, ..))| (*l, *g)).min_by(|x, y| x.1.total_cmp(&y.1)).unwrap_or((1, f64::INFINITY));
+    let mut sequence = vec![];
+    let mut k = n;
+
+    while k > 0 {
+        let m = optimal[k - 1][&l].2.clone();
+        k = m.range.start;
+        l -= 1;
+        sequence.push(m);
+    }
+
+    sequence.reverse();
+    Strength { guesses, sequence }
+
+}
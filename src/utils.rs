@@ -7,160 +7,381 @@ pub use {
 
 
 use {
-    crate::error::GenError,
-    std::{str::FromStr,sync::{Arc, atomic::{Ordering::*, AtomicUsize},},},
+    crate::{error::GenError, class::{CharClass, Table}},
+    unicode_segmentation::UnicodeSegmentation,
+    num_bigint::{BigInt, RandBigInt},
+    std::{
+        collections::{BTreeMap, HashMap},
+        ops::{Bound::*, RangeBounds},
+        sync::atomic::{Ordering::*, AtomicBool, AtomicUsize},
+    },
 };
 
 
 
 
+/// Characters which are easily mistaken for each other, skipped in the unambiguous mode, see [`RandKey::set_unambiguous`]
+///
+/// Letters and numbers follow `pwgen -B`: `B` `8` `G` `6` `I` `1` `l` `0` `O` `Q` `D` `S` `5` `Z` `2`.
+/// Symbols are the quotes and the tiny marks: `` ` `` `'` `"` `|` `,` `.` `;` `:`.
+/// Only `3`, `4`, `7` and `9` are left of the digits.
+///
+/// [`RandKey::set_unambiguous`]: crate::RandKey::set_unambiguous
+pub const AMBIGUOUS: &[&str] = &[
+    "B", "8", "G", "6", "I", "1", "l", "0", "O", "Q", "D", "S", "5", "Z", "2",
+    "`", "'", "\"", "|", ",", ".", ";", ":",
+];
+
+
 /// Characters set
 ///
-/// return letters, symbols, numbers in `Vec<Vec<String>>`
+/// return the `Alphabetic`, `Punctuation` and `Digit` classes, all of them require zero characters
 #[inline]
 #[rustfmt::skip]
-pub(crate) fn _DEFAULT_DATA() -> Vec<Vec<String>> {
+pub(crate) fn _DEFAULT_DATA() -> Vec<CharClass> {
 
-    let mut letters = Vec::new();
-    let mut symbols = Vec::new();
-    let mut numbers = Vec::new();
+    let mut letters = Table::default();
+    let mut symbols = Table::default();
+    let mut numbers = Table::default();
 
-    let _ = (33..127).into_iter()
-                     .for_each(|x| {
-                         let ch = x as u8 as char;
+    (33..127u8).for_each(|x| {
+        let ch = &[x][..];
+        let ch = std::str::from_utf8(ch).unwrap_or_default();
 
-                         if ch.is_ascii_alphabetic()  { letters.push(ch.into()) }
-                         if ch.is_ascii_punctuation() { symbols.push(ch.into()) }
-                         if ch.is_ascii_digit()       { numbers.push(ch.into()) }
-                     });
+        if x.is_ascii_alphabetic()  { letters.push(ch) }
+        if x.is_ascii_punctuation() { symbols.push(ch) }
+        if x.is_ascii_digit()       { numbers.push(ch) }
+    });
 
-    vec![letters, symbols, numbers]
+    vec![CharClass { name: "Alphabetic".into(),  cnt: 0, range: None, data: letters },
+         CharClass { name: "Punctuation".into(), cnt: 0, range: None, data: symbols },
+         CharClass { name: "Digit".into(),       cnt: 0, range: None, data: numbers },]
 
 }
 
 
-/// Count the fields of `RandKey` in a string
+/// Count the characters of every class in a string
 ///
-/// The `_CNT("ab123_c53")` returns `(3, 5, 1)`
+/// A character, which is a grapheme cluster, is counted by the first class which contains it,
+/// with the default classes, `_CNT("ab123_c53", ..)` returns `[3, 1, 5]`
 #[inline]
 #[rustfmt::skip]
-pub(crate) fn _CNT(content: impl AsRef<str>) -> Result<(BigUint, BigUint, BigUint), GenError> {
+pub(crate) fn _CNT(content: impl AsRef<str>, classes: &[CharClass]) -> Result<Vec<usize>, GenError> {
+
+    let lookup: HashMap<&str, usize> =
+        classes.iter()
+               .enumerate()
+               .rev()
+               .flat_map(|(i, class)| class.data.iter().map(move |x| (x, i)))
+               .collect();
+
+    let cnts: Vec<AtomicUsize> = classes.iter().map(|_| AtomicUsize::new(0)).collect();
+    let invalid = AtomicBool::new(false);
+    let items = content.as_ref().graphemes(true).collect::<Vec<_>>();
+
+    items.par_iter()
+         .for_each(|x| {
+             match lookup.get(x) {
+                 Some(i) => { cnts[*i].fetch_add(1, SeqCst); }
+                 None    => { invalid.store(true, SeqCst); }
+             }
+         });
+
+    if invalid.load(SeqCst) {
+        let item = items.iter().find(|x| !lookup.contains_key(*x)).copied().unwrap_or_default();
+        Err(GenError::InvalidChar(item.into()))
+    } else {
+        Ok(cnts.into_iter().map(AtomicUsize::into_inner).collect())
+    }
 
-    let l = Arc::new(AtomicUsize::new(0));
-    let s = Arc::new(AtomicUsize::new(0));
-    let n = Arc::new(AtomicUsize::new(0));
+}
 
-    content.as_ref()
-           .chars()
-           .collect::<Vec<_>>()
-           .par_iter()
-           .for_each(|x| {
-               if x.is_ascii() {
 
-                   if x.is_ascii_alphabetic()  {  l.clone().fetch_add(1, SeqCst); }
-                   if x.is_ascii_punctuation() {  s.clone().fetch_add(1, SeqCst); }
-                   if x.is_ascii_digit()       {  n.clone().fetch_add(1, SeqCst); }
+/// Lay the classes out in a random order, class `i` takes `cnts[i]` positions
+///
+/// Each position picks its class with the probability in proportion to the positions the class has left,
+/// so every arrangement is equally likely without shuffling. The characters are picked afterwards.
+#[inline]
+pub(crate) fn _LAYOUT<R: Rng + ?Sized>(cnts: &[usize], rng: &mut R) -> Result<Vec<u32>, GenError> {
+    let mut left = cnts.to_vec();
+    let mut total = left.iter().try_fold(0usize, |sum, x| sum.checked_add(*x)).ok_or_else(|| _TOO_LARGE(cnts))?;
+    let mut layout = Vec::new();
+    layout.try_reserve_exact(total).map_err(|_| _TOO_LARGE(cnts))?;
+
+    while total > 0 {
+        let i = _CLASS_AT(&left, _BELOW(total, rng));
+
+        layout.push(i as u32);
+        left[i] -= 1;
+        total -= 1;
+
+        // The other classes are used up
+        if left[i] == total {
+            layout.resize(layout.len() + total, i as u32);
+            break;
+        }
+    }
 
-               }
-           });
+    Ok(layout)
+}
 
-    let l = l.load(SeqCst).to_biguint().unwrap();
-    let s = s.load(SeqCst).to_biguint().unwrap();
-    let n = n.load(SeqCst).to_biguint().unwrap();
 
-    if &l+&s+&n != content.as_ref().len().to_biguint().unwrap() {
-        Err(GenError::InvalidChar)
-    } else {
-        Ok((l, s, n))
+/// Return the class which position `r` falls in, when class `i` takes `left[i]` positions in a row
+#[inline]
+pub(crate) fn _CLASS_AT(left: &[usize], mut r: usize) -> usize {
+    let last = left.len().saturating_sub(1);
+
+    for (i, x) in left[..last].iter().enumerate() {
+        if r < *x {
+            return i;
+        }
+        r -= x;
     }
 
+    last
 }
 
 
-/// Generate n random numbers, each one is up to `length`
+/// The error of a key which has more characters than the memory holds
 #[inline]
-pub(crate) fn _RAND_IDX(cnt: &BigUint, length: usize) -> Vec<usize> {
-    let mut n = cnt.to_biguint().unwrap();
-    let mut idxs = Vec::with_capacity(n.to_usize().unwrap());
+pub(crate) fn _TOO_LARGE(cnts: &[usize]) -> GenError {
+    GenError::TooLarge(cnts.iter().map(|x| BigUint::from(*x)).sum::<BigUint>().to_string())
+}
 
-    while !n.is_zero() {
-        idxs.push(thread_rng().gen_range(0, length));
-        n -= BigUint::one();
+
+/// A uniform random number below `n`, which must be positive
+///
+/// It's the multiply-and-shift method of Lemire, the division only happens on the rare rejections.
+#[inline]
+pub(crate) fn _BELOW<R: Rng + ?Sized>(n: usize, rng: &mut R) -> usize {
+    let n = n as u64;
+    let mut m = u128::from(rng.next_u64()) * u128::from(n);
+
+    if (m as u64) < n {
+        let threshold = n.wrapping_neg() % n;
+        while (m as u64) < threshold {
+            m = u128::from(rng.next_u64()) * u128::from(n);
+        }
     }
 
-    idxs
+    (m >> 64) as usize
+}
+
 
+/// Return the bytes a key of `cnts[i]` characters from `tables[i]` takes at most
+///
+/// It's an error if they don't fit in the addressable memory.
+#[inline]
+pub(crate) fn _CAPACITY(tables: &[&Table], cnts: &[usize]) -> Result<usize, GenError> {
+    tables.iter()
+          .zip(cnts)
+          .try_fold(0usize, |sum, (table, cnt)| cnt.checked_mul(table.max_width()).and_then(|x| sum.checked_add(x)))
+          .filter(|x| *x <= isize::MAX as usize)
+          .ok_or_else(|| _TOO_LARGE(cnts))
 }
 
 
-/// Resolve large numbers into smaller numbers
+/// Pick the count of every class, uniformly among all the compositions whose sum falls in `total`
+///
+/// `bounds` holds the inclusive minimum and maximum of each class, it's `InvalidRange` if no composition fits.
+/// The compositions are counted in closed form, so neither the time nor the memory grows with the length.
 #[inline]
-pub(crate) fn _DIV_UNIT(unit: &BigUint, n: &mut BigUint) -> Vec<BigUint> {
-    let UNIT = unit.to_biguint().unwrap();
+pub(crate) fn _COMPOSE<R: Rng + ?Sized>(bounds: &[(usize, usize)], total: (usize, usize), rng: &mut R) -> Result<Vec<usize>, GenError> {
+    if bounds.iter().any(|(min, max)| min > max) {
+        return Err(GenError::InvalidRange);
+    }
 
-    let mut ret = Vec::with_capacity((n.clone() / &UNIT + BigUint::one()).to_usize().unwrap());
+    // Every class starts from zero once its minimum is taken away
+    let base = bounds.iter().try_fold(0usize, |sum, x| sum.checked_add(x.0)).ok_or(GenError::InvalidRange)?;
+    let top = bounds.iter().fold(0usize, |sum, x| sum.saturating_add(x.1));
+    let (lo, hi) = (total.0.max(base), total.1.min(top));
 
-    loop {
-        if n.clone() < UNIT {
-            ret.push(n.to_biguint().unwrap());
-            break;
-        } else {
-            *n -= UNIT.clone();
-            ret.push(UNIT.clone());
-        }
+    if lo > hi {
+        return Err(GenError::InvalidRange);
     }
 
-    ret
+    let (lo, hi) = (lo - base, hi - base);
+    let spans: Vec<usize> = bounds.iter().map(|(min, max)| max - min).collect();
 
+    // ways[i] counts the compositions of the classes from `i` on
+    let mut ways = vec![Ways::new()];
+    for span in spans.iter().rev() {
+        let next = ways[ways.len() - 1].with(*span, hi)?;
+        ways.push(next);
+    }
+    ways.reverse();
+
+    let before = ways[0].below(lo);
+    let sum = ways[0].upto(hi) - &before;
+    if sum.is_zero() {
+        return Err(GenError::InvalidRange);
+    }
+
+    let r = rng.gen_biguint_below(&sum) + &before;
+    let mut t = _FIRST(lo, hi, |t| ways[0].upto(t) > r);
+
+    let mut cnts = Vec::with_capacity(bounds.len());
+    for (i, (min, span)) in bounds.iter().map(|x| x.0).zip(&spans).enumerate() {
+        // The compositions of the rest, which sum to some `x` in `start..=t`, leave `t - x` to this class
+        let rest = &ways[i + 1];
+        let start = t.saturating_sub(*span);
+        let before = rest.below(start);
+        let r = rng.gen_biguint_below(&(rest.upto(t) - &before)) + before;
+        let c = t - _FIRST(start, t, |x| rest.upto(x) > r);
+
+        cnts.push(min + c);
+        t -= c;
+    }
+
+    Ok(cnts)
 }
 
 
-/// Check whether the elements in the sequence are all ascii values
-#[inline]
-pub(crate) fn _CHECK_ASCII(v: &[impl AsRef<str>]) -> bool {
-    v.iter()
-     .find(|c| {
-            let c = _CHAR_FROM_STR(c);
-            !c.is_ascii() || c.is_ascii_control()
-        })
-     .is_none()
+/// The number of compositions of some classes, whose sum is at most `n`, in closed form
+///
+/// With `k` classes free from `0` to `span`, it's the sum of `coef * C(n - exp + k, k)` over the terms,
+/// which come from the inclusion-exclusion of the classes that exceed their spans.
+#[derive(Clone, Debug)]
+struct Ways {
+    k:     usize,
+    terms: Vec<(usize, BigInt)>,
 }
 
 
-#[inline]
-pub(crate) fn _GROUP(v: &[impl AsRef<str>]) -> Vec<Vec<String>> {
+/// The most terms `Ways` keeps, it's only reached with a lot of classes of distinct ranges
+const _MAX_TERMS: usize = 1 << 16;
 
-    use parking_lot::Mutex;
 
-    let v: Vec<String> = v.iter().map(|x| x.as_ref().to_string()).collect();
+impl Ways {
+    /// No class, there's one composition of every sum
+    #[inline]
+    fn new() -> Self { Ways { k: 0, terms: vec![(0, BigInt::one())] } }
 
-    let ltr = Mutex::new(Vec::<String>::new());
-    let sbl = Mutex::new(Vec::<String>::new());
-    let num = Mutex::new(Vec::<String>::new());
+    /// Add a class of `0..=span`, the terms beyond `hi` are dropped since they never count
+    #[inline]
+    fn with(&self, span: usize, hi: usize) -> Result<Self, GenError> {
+        if span == 0 {
+            return Ok(self.clone());
+        }
 
-    v.par_iter().for_each(|c| {
-        let mut temp;
-        let c = _CHAR_FROM_STR(c);
+        let mut terms: BTreeMap<usize, BigInt> = self.terms.iter().cloned().collect();
 
-        if c.is_ascii_alphabetic() {
-            temp = ltr.lock();
-            temp.push(c.clone().into());
+        if let Some(step) = span.checked_add(1).filter(|x| *x <= hi) {
+            for (exp, coef) in &self.terms {
+                if let Some(exp) = exp.checked_add(step).filter(|x| *x <= hi) {
+                    *terms.entry(exp).or_insert_with(BigInt::zero) -= coef;
+                }
+            }
         }
-        if c.is_ascii_punctuation() {
-            temp = sbl.lock();
-            temp.push(c.clone().into());
+
+        terms.retain(|_, coef| !coef.is_zero());
+        if terms.len() > _MAX_TERMS {
+            return Err(GenError::TooLarge(hi.to_string()));
         }
-        if c.is_ascii_digit() {
-            temp = num.lock();
-            temp.push(c.clone().into());
+
+        Ok(Ways { k: self.k + 1, terms: terms.into_iter().collect() })
+    }
+
+    /// The number of compositions whose sum is at most `n`
+    #[inline]
+    fn upto(&self, n: usize) -> BigUint {
+        let sum: BigInt =
+            self.terms
+                .iter()
+                .take_while(|(exp, _)| *exp <= n)
+                .map(|(exp, coef)| coef * BigInt::from(_CHOOSE(n - exp, self.k)))
+                .sum();
+
+        sum.to_biguint().unwrap_or_default()
+    }
+
+    /// The number of compositions whose sum is below `n`
+    #[inline]
+    fn below(&self, n: usize) -> BigUint { n.checked_sub(1).map(|n| self.upto(n)).unwrap_or_default() }
+}
+
+
+/// `C(m + k, k)`, the number of ways `k` classes sum to at most `m`
+#[inline]
+fn _CHOOSE(m: usize, k: usize) -> BigUint {
+    (1..=k).fold(BigUint::one(), |x, j| x * (BigUint::from(m) + j) / j)
+}
+
+
+/// The smallest `x` in `lo..=hi` which meets `pred`, which holds from some point on and at `hi`
+#[inline]
+fn _FIRST(mut lo: usize, mut hi: usize, pred: impl Fn(usize) -> bool) -> usize {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
         }
-    });
+    }
+
+    lo
+}
 
-    vec![ltr.into_inner(), sbl.into_inner(), num.into_inner()]
 
+/// Turn a range into its inclusive minimum and maximum, the maximum is `None` if unbounded
+///
+/// It's `InvalidRange` if the range is empty.
+#[inline]
+pub(crate) fn _BOUNDS(range: impl RangeBounds<usize>) -> Result<(usize, Option<usize>), GenError> {
+    let min = match range.start_bound() {
+        Included(x) => Some(*x),
+        Excluded(x) => x.checked_add(1),
+        Unbounded => Some(0),
+    };
+    let max = match range.end_bound() {
+        Included(x) => Some(Some(*x)),
+        Excluded(x) => x.checked_sub(1).map(Some),
+        Unbounded => Some(None),
+    };
+
+    match (min, max) {
+        (Some(min), Some(max)) if !matches!(max, Some(max) if min > max) => Ok((min, max)),
+        _ => Err(GenError::InvalidRange),
+    }
 }
 
 
+/// Check whether the elements in the sequence are all single characters excluded control ones
+///
+/// A single character is one grapheme cluster, like `a`, `ж`, `字` or `🦀️`, the first one which isn't is returned in the error.
 #[inline]
-pub(crate) fn _CHAR_FROM_STR(s: impl AsRef<str>) -> char { char::from_str(s.as_ref()).unwrap() }
+pub(crate) fn _CHECK_ITEM(v: &[impl AsRef<str>]) -> Result<(), GenError> {
+    match v.iter().map(AsRef::as_ref).find(|x| x.graphemes(true).count() != 1 || x.chars().any(char::is_control)) {
+        Some(x) => Err(GenError::InvalidChar(x.into())),
+        None => Ok(()),
+    }
+}
+
 
+/// Group the items into letters, symbols and numbers by their first `char`
+///
+/// Whitespaces belong to none of them.
+#[inline]
+#[rustfmt::skip]
+pub(crate) fn _GROUP(v: &[impl AsRef<str>]) -> Vec<Vec<String>> {
+
+    let mut ltr = Vec::<String>::new();
+    let mut sbl = Vec::<String>::new();
+    let mut num = Vec::<String>::new();
+
+    v.iter().for_each(|x| {
+        let x = x.as_ref();
+
+        match x.chars().next() {
+            Some(c) if c.is_alphabetic()  => ltr.push(x.into()),
+            Some(c) if c.is_numeric()     => num.push(x.into()),
+            Some(c) if !c.is_whitespace() => sbl.push(x.into()),
+            _ => (),
+        }
+    });
+
+    vec![ltr, sbl, num]
+
+}
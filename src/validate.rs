@@ -0,0 +1,179 @@
+use {
+    crate::{RandKey, class::CharClass},
+    std::collections::HashMap,
+    unicode_segmentation::UnicodeSegmentation,
+};
+
+
+
+
+/// Checks candidates, like the passwords users pick, against the policy of a `RandKey`
+///
+/// The policy is the length, the count or range of every class and the characters of the classes,
+/// the look-alikes are forbidden in the unambiguous mode. On top of it, some characters may be forbidden
+/// and the runs of one character may be limited, which only apply to the candidates, not to `join`.
+/// # Example
+///
+/// Basic usage:
+/// ```
+/// use rand_key::{RandKey, Violation};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut r_p = RandKey::new("0", "0", "0")?;
+/// r_p.set_range("Alphabetic", 6..)?;
+/// r_p.set_range("Digit", 2..)?;
+/// r_p.set_len_range(8..=64)?;
+///
+/// let validator = r_p.validator().forbid(&[" "]).max_repeat(2);
+/// assert!(validator.validate("hunter2024").is_empty());
+///
+/// let violations = validator.validate("aaa bc1");
+/// assert_eq!(violations, [
+///     Violation::Length { len: 7, min: 8, max: Some(64) },
+///     Violation::Count { class: "Alphabetic".into(), cnt: 5, min: 6, max: None },
+///     Violation::Count { class: "Digit".into(), cnt: 1, min: 2, max: None },
+///     Violation::Forbidden(" ".into()),
+///     Violation::Repeat { item: "a".into(), run: 3, max: 2 },
+/// ]);
+/// println!("{}", violations[0]);
+/// // The length is 7, but it has to be between 8 and 64
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Validator<'a> {
+    policy:     &'a RandKey,
+    forbidden:  Vec<String>,
+    max_repeat: Option<usize>,
+}
+
+
+/// A rule of the policy a candidate breaks, see [`Validator`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The number of characters is out of the range of length
+    Length { len: usize, min: usize, max: Option<usize> },
+    /// The number of characters of the class is out of its count or range
+    Count { class: String, cnt: usize, min: usize, max: Option<usize> },
+    /// The character is in none of the classes
+    NotAllowed(String),
+    /// The character is forbidden, or is a look-alike in the unambiguous mode
+    Forbidden(String),
+    /// The character is repeated more times in a row than allowed, `run` is its longest run
+    Repeat { item: String, run: usize, max: usize },
+}
+
+
+impl RandKey {
+    /// Return a validator of the policy, see [`Validator`]
+    #[inline]
+    pub fn validator(&self) -> Validator<'_> { Validator { policy: self, forbidden: vec![], max_repeat: None } }
+
+    /// Return every rule of the policy `candidate` breaks, it's valid if there is none
+    ///
+    /// It's the same as `r_p.validator().validate(candidate)`.
+    #[inline]
+    pub fn validate(&self, candidate: &str) -> Vec<Violation> { self.validator().validate(candidate) }
+}
+
+
+impl Validator<'_> {
+    /// Forbid some characters, even if they are in some class
+    #[inline]
+    pub fn forbid(mut self, items: &[impl AsRef<str>]) -> Self {
+        self.forbidden.extend(items.iter().map(|x| x.as_ref().to_string()));
+        self
+    }
+
+    /// Allow a character to be repeated at most `max` times in a row
+    #[inline]
+    pub fn max_repeat(mut self, max: usize) -> Self {
+        self.max_repeat = Some(max);
+        self
+    }
+
+    /// Return every rule `candidate` breaks, in the order of the length, the classes and the characters
+    ///
+    /// Every character is reported once, at its first occurrence.
+    #[inline]
+    #[rustfmt::skip]
+    pub fn validate(&self, candidate: &str) -> Vec<Violation> {
+
+        let policy = self.policy;
+        let DATA = policy.active_data();
+        let items: Vec<&str> = candidate.graphemes(true).collect();
+        let mut violations = vec![];
+
+        // The first class of a character counts it, like `_CNT`
+        let lookup = |classes: &[CharClass]| -> HashMap<String, usize> {
+            classes.iter()
+                   .enumerate()
+                   .rev()
+                   .flat_map(|(i, class)| class.data.iter().map(move |x| (x.to_string(), i)))
+                   .collect()
+        };
+        let (active, all) = (lookup(&DATA), lookup(&policy.DATA));
+
+        let mut cnts = vec![0; DATA.len()];
+        let mut chars = vec![];
+
+        for x in &items {
+            if let Some(i) = active.get(*x) {
+                cnts[*i] += 1;
+            }
+
+            if self.forbidden.iter().any(|y| y == x) || !active.contains_key(*x) && all.contains_key(*x) {
+                chars.push(Violation::Forbidden(x.to_string()));
+            } else if !active.contains_key(*x) {
+                chars.push(Violation::NotAllowed(x.to_string()));
+            }
+        }
+
+        let bounds: Vec<_> = DATA.iter().map(|class| class.range.unwrap_or((class.cnt, Some(class.cnt)))).collect();
+
+        let exact: usize = bounds.iter().map(|x| x.0).sum();
+
+        // Without a range of length, the ranges of classes are all the bounds of a ranged policy
+        let len = match policy.LEN {
+            Some(len) => Some(len),
+            None if !policy.is_ranged() => Some((exact, Some(exact))),
+            None => None,
+        };
+        let outside = |x: usize, (min, max): (usize, Option<usize>)| !(min..=max.unwrap_or(usize::MAX)).contains(&x);
+
+        if let Some((min, max)) = len {
+            if outside(items.len(), (min, max)) {
+                violations.push(Violation::Length { len: items.len(), min, max });
+            }
+        }
+
+        for ((class, (min, max)), cnt) in DATA.iter().zip(bounds).zip(cnts) {
+            if outside(cnt, (min, max)) {
+                violations.push(Violation::Count { class: class.name.clone(), cnt, min, max });
+            }
+        }
+
+        chars.into_iter().for_each(|x| if !violations.contains(&x) { violations.push(x) });
+
+        if let Some(max) = self.max_repeat {
+            let mut start = 0;
+
+            for end in 1..=items.len() {
+                if end == items.len() || items[end] != items[start] {
+                    let (item, run) = (items[start].to_string(), end - start);
+
+                    match violations.iter_mut().find(|x| matches!(x, Violation::Repeat { item: y, .. } if *y == item)) {
+                        Some(Violation::Repeat { run: longest, .. }) => *longest = run.max(*longest),
+                        _ if run > max => violations.push(Violation::Repeat { item, run, max }),
+                        _ => {}
+                    }
+
+                    start = end;
+                }
+            }
+        }
+
+        violations
+
+    }
+}
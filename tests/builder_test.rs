@@ -0,0 +1,45 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Builder {
+
+    use rand_key::{RandKey, GenError, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn steps_come_in_any_order() {
+        let r_p = RandKey::builder().data(&["a", "b"]).cnt(Punctuation, 0).cnt(Alphabetic, 8).build().unwrap();
+
+        r_p.join().unwrap();
+        assert!(r_p.key().chars().all(|x| x == 'a' || x == 'b'));
+    }
+
+    #[test]
+    fn exclusions_win_over_added_items() {
+        let r_p = RandKey::builder().exclude(&["ж"]).add_item(&["ж", "я"]).cnt(Alphabetic, 50).build().unwrap();
+
+        assert!(!r_p.data(Alphabetic).unwrap().contains(&"ж"));
+        assert!(r_p.data(Alphabetic).unwrap().contains(&"я"));
+    }
+
+    #[test]
+    fn first_error_is_returned() {
+        let result = RandKey::builder().cnt("Hex", 3).unit(0).add_item(&["ab"]).build();
+        assert!(matches!(result, Err(GenError::UnknownClass(x)) if x == "Hex"));
+
+        let result = RandKey::builder().unit(0).cnt(Digit, -1).build();
+        assert!(matches!(result, Err(GenError::InvalidUnit)));
+    }
+
+    #[test]
+    fn data_and_ranges_are_validated_once() {
+        let result = RandKey::builder().cnt(Digit, 2).exclude(&["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]).build();
+        assert!(matches!(result, Err(GenError::MissChar(x)) if x == "Digit"));
+
+        let result = RandKey::builder().range(Digit, 5..).len_range(..4).build();
+        assert!(matches!(result, Err(GenError::InvalidRange)));
+
+        let result = RandKey::builder().data(&["a", "0"]).cnt(Digit, 1).unambiguous(true).build();
+        assert!(matches!(result, Err(GenError::OnlyAmbiguousChar(x)) if x == "Digit"));
+    }
+}
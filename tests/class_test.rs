@@ -0,0 +1,83 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod CustomClass {
+
+    use rand_key::{RandKey, CharClass, SetRandKeyOp::Check};
+
+    fn classes() -> RandKey {
+        let upper: Vec<String> = ('A'..='Z').map(String::from).collect();
+        let lower: Vec<String> = ('a'..='z').map(String::from).collect();
+        let hex: Vec<String> = "0123456789abcdef".chars().map(String::from).collect();
+
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        for kind in &["Alphabetic", "Punctuation", "Digit"] {
+            r_p.del_class(kind).unwrap();
+        }
+        r_p.add_class(CharClass::new("Upper", &upper, "4").unwrap()).unwrap();
+        r_p.add_class(CharClass::new("Lower", &lower, "5").unwrap()).unwrap();
+        r_p.add_class(CharClass::new("Hex", &hex, "6").unwrap()).unwrap();
+        r_p.add_class(CharClass::new("Safe", &["!", "#", "%"], "2").unwrap()).unwrap();
+        r_p
+    }
+
+    #[test]
+    fn join_respects_every_class() {
+        let mut r_p = classes();
+        r_p.set_cnt("Lower", "0").unwrap();
+        r_p.join().unwrap();
+
+        let key = r_p.key();
+        assert_eq!(key.len(), 12);
+        assert_eq!(key.chars().filter(char::is_ascii_uppercase).count(), 4);
+        assert_eq!(key.chars().filter(|x| "!#%".contains(*x)).count(), 2);
+        assert!(key.chars().all(|x| x.is_ascii_uppercase() || x.is_ascii_hexdigit() || "!#%".contains(x)));
+    }
+
+    #[test]
+    fn first_class_counts_a_shared_character() {
+        let mut r_p = classes();
+        r_p.set_cnt("Upper", "1").unwrap();
+        r_p.set_cnt("Lower", "2").unwrap();
+        r_p.set_cnt("Hex", "1").unwrap();
+        r_p.set_cnt("Safe", "0").unwrap();
+
+        // `a` and `b` belong to `Lower` first, only `7` is counted as `Hex`
+        assert!(r_p.set_key("Zab7", Check).is_ok());
+        assert!(r_p.set_key("Za77", Check).is_err());
+    }
+
+    #[test]
+    fn missing_data_is_reported() {
+        let mut r_p = classes();
+        r_p.clear("Safe").unwrap();
+        assert!(r_p.join().is_err());
+
+        r_p.set_cnt("Safe", "0").unwrap();
+        assert!(r_p.join().is_ok());
+    }
+
+    #[test]
+    fn packed_data_follows_every_change() {
+        let mut r_p = RandKey::new("6", "0", "0").unwrap();
+        r_p.replace_data(&["a", "b", ".", "1"]).unwrap();
+
+        // The widths differ once `ж` and `字` join the letters
+        r_p.add_item(&["ж", "字", "a"]).unwrap();
+        assert_eq!(r_p.data("Alphabetic").unwrap(), ["a", "b", "ж", "字"]);
+        r_p.join().unwrap();
+        assert!(r_p.key().chars().all(|x| "abж字".contains(x)));
+
+        r_p.del_item(&["a", "b"]).unwrap();
+        assert_eq!(r_p.class("Alphabetic").unwrap().data(), ["ж", "字"]);
+        r_p.join().unwrap();
+        assert_eq!(r_p.key().chars().count(), 6);
+        assert!(r_p.key().chars().all(|x| "ж字".contains(x)));
+
+        r_p.del_item(&["ж"]).unwrap();
+        r_p.join().unwrap();
+        assert_eq!(r_p.key(), "字".repeat(6));
+        assert_eq!(r_p.class("Alphabetic").unwrap().len(), 1);
+    }
+}
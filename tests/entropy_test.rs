@@ -0,0 +1,124 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Entropy {
+
+    use num_bigint::BigUint;
+    use rand_key::{RandKey, CharClass, GenError, MAX_RANGED_LEN, ASCIIExcludeCtrl::*};
+
+    fn binary() -> RandKey {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.clear_all();
+        r_p.add_class(CharClass::new("Bit", &["0", "1"], "0").unwrap()).unwrap();
+        r_p
+    }
+
+    #[test]
+    fn default_classes() {
+        let r_p = RandKey::new("10", "2", "3").unwrap();
+        let entropy = r_p.entropy().unwrap();
+
+        // 15! / (10! 2! 3!) arrangements
+        let expected = BigUint::from(30030u32)
+            * BigUint::from(52u32).pow(10)
+            * BigUint::from(32u32).pow(2)
+            * BigUint::from(10u32).pow(3);
+
+        assert_eq!(entropy.keyspace(), &expected);
+        assert!((entropy.bits() - 91.8443).abs() < 1e-3, "{}", entropy.bits());
+    }
+
+    #[test]
+    fn multinomials_of_long_keys() {
+        let fact = |n: u32| (1..=n).fold(BigUint::from(1u32), |x, i| x * i);
+
+        for (l, s, n) in [(300, 200, 100), (1, 0, 997), (64, 64, 0), (0, 0, 0)] {
+            let r_p = RandKey::new(l, s, n).unwrap();
+            let entropy = r_p.entropy().unwrap();
+
+            let expected = fact(l + s + n) / (fact(l) * fact(s) * fact(n))
+                * BigUint::from(52u32).pow(l)
+                * BigUint::from(32u32).pow(s)
+                * BigUint::from(10u32).pow(n);
+
+            assert_eq!(entropy.keyspace(), &expected);
+            assert!(entropy.bits() <= expected.bits() as f64 && entropy.bits() >= expected.bits() as f64 - 1.0);
+        }
+
+        // Tens of thousands of characters of each class don't take long
+        let entropy = RandKey::new("30000", "30000", "30000").unwrap().entropy().unwrap();
+        assert_eq!(entropy.keyspace().bits(), 563301);
+        assert!((entropy.bits() - 563300.92775).abs() < 1e-3, "{}", entropy.bits());
+    }
+
+    #[test]
+    fn ranges_pick_the_composition_first() {
+        let mut r_p = binary();
+        r_p.set_range("Bit", 1..=2).unwrap();
+
+        // 2 keys of length 1 and 4 keys of length 2, either length is picked by half
+        let entropy = r_p.entropy().unwrap();
+        assert_eq!(entropy.keyspace(), &BigUint::from(6u32));
+        assert!((entropy.bits() - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn long_ranges_are_counted_by_length() {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.set_range(Alphabetic, ..).unwrap();
+        r_p.set_range(Punctuation, ..).unwrap();
+        r_p.set_range(Digit, ..).unwrap();
+        r_p.set_len_range(8..=64).unwrap();
+
+        // Every string of the 94 characters has exactly one composition
+        let expected: BigUint = (8..=64).map(|t| BigUint::from(94u32).pow(t)).sum();
+        assert_eq!(r_p.keyspace().unwrap(), expected);
+
+        r_p.set_len_range(8..=MAX_RANGED_LEN + 1).unwrap();
+        assert!(matches!(r_p.entropy(), Err(GenError::LongRange { len, .. }) if len == MAX_RANGED_LEN + 1));
+        assert!(r_p.rank("abc").is_err());
+    }
+
+    #[test]
+    fn look_alikes_are_left_out() {
+        let mut r_p = RandKey::new("0", "0", "3").unwrap();
+        r_p.set_unambiguous(true);
+
+        // 0, 1, 2, 5, 6 and 8 are skipped
+        assert_eq!(r_p.entropy().unwrap().keyspace(), &BigUint::from(64u32));
+
+        r_p.set_cnt(Digit, "0").unwrap();
+        assert_eq!(r_p.entropy().unwrap().bits(), 0.0);
+    }
+
+    #[test]
+    fn with_entropy_is_the_shortest() {
+        let r_p = RandKey::with_entropy(128.0, None).unwrap();
+        let cnts: Vec<usize> = [Alphabetic, Punctuation, Digit].iter().map(|x| r_p.get_cnt(x)).collect();
+        let len: usize = cnts.iter().sum();
+
+        assert!(r_p.entropy().unwrap().bits() >= 128.0);
+
+        // No composition one character shorter makes it
+        let mut shorter = RandKey::new("0", "0", "0").unwrap();
+        for ltr in 0..len {
+            for sbl in 0..len - ltr {
+                shorter.set_cnt(Alphabetic, ltr.to_string()).unwrap();
+                shorter.set_cnt(Punctuation, sbl.to_string()).unwrap();
+                shorter.set_cnt(Digit, (len - 1 - ltr - sbl).to_string()).unwrap();
+                assert!(shorter.entropy().unwrap().bits() < 128.0);
+            }
+        }
+    }
+
+    #[test]
+    fn unreachable_entropy() {
+        assert!(binary().fit_entropy(f64::INFINITY, None).is_err());
+        assert!(binary().fit_entropy(8.0, Some(&[0, 0, 0, 0])).is_err());
+
+        let mut r_p = binary();
+        r_p.fit_entropy(8.0, None).unwrap();
+        assert_eq!(r_p.get_cnt("Bit"), 8);
+    }
+}
@@ -27,3 +27,94 @@ mod InvalidInput {
         r_p.join().unwrap();
     }
 }
+
+
+#[cfg(test)]
+mod Unambiguous {
+
+    use num_bigint::BigUint;
+    use rand_key::{RandKey, GenError, AMBIGUOUS, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn emptied_class_is_reported() {
+        let mut r_p = RandKey::new("1", "1", "1").unwrap();
+        r_p.clear(Digit).unwrap();
+        r_p.add_item(&["0", "1", "5"]).unwrap();
+        r_p.set_unambiguous(true);
+
+        assert!(matches!(r_p.join(), Err(GenError::OnlyAmbiguousChar(x)) if x == "Digit"));
+
+        r_p.set_cnt(Digit, "0").unwrap();
+        assert!(r_p.join().is_ok());
+    }
+
+    #[test]
+    fn only_four_digits_are_left() {
+        let mut r_p = RandKey::new("0", "0", "1").unwrap();
+        r_p.set_unambiguous(true);
+
+        assert_eq!(AMBIGUOUS.len(), 23);
+        assert_eq!(r_p.keyspace().unwrap(), BigUint::from(4u32));
+        assert_eq!(r_p.unrank(&BigUint::from(0u32)).unwrap().as_str(), "3");
+        assert_eq!(r_p.unrank(&BigUint::from(3u32)).unwrap().as_str(), "9");
+
+        r_p.set_cnt(Alphabetic, "1").unwrap();
+        r_p.set_cnt(Punctuation, "1").unwrap();
+        r_p.set_cnt(Digit, "0").unwrap();
+        assert_eq!(r_p.keyspace().unwrap(), BigUint::from(2u32 * 43 * 24));
+    }
+}
+
+
+#[cfg(test)]
+mod TooLarge {
+
+    use rand_key::{RandKey, GenError, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn count_beyond_usize_is_rejected() {
+        let huge = "1".repeat(40);
+
+        assert!(matches!(RandKey::new(&huge, "0", "0"), Err(GenError::TooLarge(x)) if x == huge));
+        assert!(matches!(RandKey::default().set_cnt(Digit, &huge), Err(GenError::TooLarge(_))));
+    }
+
+    #[test]
+    fn count_beyond_memory_is_an_error() {
+        let r_p = RandKey::new("9000000000000000000", "0", "1").unwrap();
+
+        assert!(matches!(r_p.join(), Err(GenError::TooLarge(x)) if x == "9000000000000000001"));
+        assert!(matches!(r_p.generate(), Err(GenError::TooLarge(_))));
+    }
+}
+
+
+#[cfg(test)]
+mod Context {
+
+    use rand_key::{RandKey, CharClass, GenError, SetRandKeyOp::*, ToRandKey, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn errors_name_the_input() {
+        assert!(matches!(RandKey::new("3", "x1", 0), Err(GenError::InvalidNumber(x)) if x == "x1"));
+        assert!(matches!(RandKey::new(-2, 0, 0), Err(GenError::InvalidNumber(x)) if x == "-2"));
+        assert!(matches!(RandKey::default().add_item(&["a", "bc"]), Err(GenError::InvalidChar(x)) if x == "bc"));
+        assert!(matches!(CharClass::new("Bell", &["\u{7}"], 1), Err(GenError::InvalidChar(x)) if x == "\u{7}"));
+        assert!(matches!("ab\u{7}".to_randkey(), Err(GenError::InvalidChar(x)) if x == "\u{7}"));
+        assert!(matches!(RandKey::default().del_item(&["字"]), Err(GenError::DelNonExistValue(x)) if x == ["字"]));
+    }
+
+    #[test]
+    fn errors_name_the_class() {
+        let mut r_p = RandKey::new(2, 1, 1).unwrap();
+
+        assert!(matches!(r_p.set_key("ab-12", Check), Err(GenError::InconsistentField(x)) if x == "Digit"));
+        assert!(matches!(r_p.del_class("Hex"), Err(GenError::UnknownClass(x)) if x == "Hex"));
+        assert!(matches!(r_p.clear("Hex"), Err(GenError::UnknownClass(x)) if x == "Hex"));
+        assert!(matches!(r_p.data("Hex"), Err(GenError::UnknownClass(x)) if x == "Hex"));
+        assert!(matches!(r_p.add_class(CharClass::new("Digit", &["0"], 1).unwrap()), Err(GenError::DuplicateClass(x)) if x == "Digit"));
+
+        r_p.clear(Punctuation).unwrap();
+        assert!(matches!(r_p.join(), Err(GenError::MissChar(x)) if x == "Punctuation"));
+    }
+}
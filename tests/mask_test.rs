@@ -0,0 +1,65 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Mask {
+
+    use rand_key::{Mask, GenError, RngSource};
+
+    #[test]
+    fn every_position_keeps_its_charset() {
+        let mask = Mask::new("?a?A?h?H??-字").unwrap();
+        assert_eq!(mask.len(), 7);
+
+        for _ in 0..200 {
+            let key: Vec<char> = mask.generate().unwrap().chars().collect();
+
+            assert!(key[0].is_ascii_graphic());
+            assert!(key[1].is_ascii_alphabetic());
+            assert!(key[2].is_ascii_digit() || ('a'..='f').contains(&key[2]));
+            assert!(key[3].is_ascii_digit() || ('A'..='F').contains(&key[3]));
+            assert_eq!(key[4..], ['?', '-', '字']);
+        }
+
+        // 94 * 52 * 16 * 16
+        assert_eq!(mask.entropy().keyspace().to_string(), "1251328");
+    }
+
+    #[test]
+    fn custom_charsets_are_drawn_uniformly() {
+        let mut mask = Mask::with_charsets("?1", &[('1', "ab?d?db")]).unwrap();
+        mask.set_rng(RngSource::Seeded([1; 32]));
+
+        let mut hits = [0; 12];
+        for _ in 0..12000 {
+            let key = mask.generate_with(&mut rand::thread_rng()).unwrap();
+            let i = "ab0123456789".find(key.as_str()).unwrap();
+            hits[i] += 1;
+        }
+        assert!(hits.iter().all(|x| (800..1200).contains(x)), "{:?}", hits);
+
+        assert_eq!(mask.generate().unwrap(), mask.generate().unwrap());
+    }
+
+    #[test]
+    fn templates_use_bare_placeholders() {
+        let mask = Mask::template("LLLL-DDDD ?d", &[('L', "?u"), ('D', "🦀😀")]).unwrap();
+        let key = mask.generate().unwrap();
+        let chars: Vec<char> = key.chars().collect();
+
+        assert_eq!(mask.len(), 11);
+        assert!(chars[..4].iter().all(char::is_ascii_uppercase));
+        assert!(chars[5..9].iter().all(|x| "🦀😀".contains(*x)));
+        assert!(chars[10].is_ascii_digit());
+    }
+
+    #[test]
+    fn invalid_masks_are_rejected() {
+        assert!(matches!(Mask::new("?u?z"), Err(GenError::InvalidMask(x)) if x == "?z"));
+        assert!(matches!(Mask::new("?u?"), Err(GenError::InvalidMask(x)) if x == "?"));
+        assert!(matches!(Mask::with_charsets("?1", &[('1', "?1")]), Err(GenError::InvalidMask(x)) if x == "?1"));
+        assert!(matches!(Mask::with_charsets("?1", &[('1', "")]), Err(GenError::MissChar(x)) if x == "?1"));
+        assert!(matches!(Mask::new("a\u{7}"), Err(GenError::InvalidChar(x)) if x == "\u{7}"));
+        assert!(Mask::new("").unwrap().generate().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Passphrase {
+
+    use std::{collections::HashSet, env, fs};
+    use rand_key::{RandPhrase, Wordlist, Case, RngSource};
+
+    #[test]
+    fn words_come_from_the_list() {
+        let mut r_p = RandPhrase::new(8);
+        r_p.set_separator(".");
+        r_p.set_case(Case::Upper);
+        r_p.join().unwrap();
+
+        let phrase = r_p.phrase();
+        let words: Vec<&str> = phrase.split('.').collect();
+        assert_eq!(words.len(), 8);
+        assert!(words.iter().all(|x| r_p.wordlist().words().contains(&x.to_lowercase())));
+    }
+
+    #[test]
+    fn digit_and_symbol_are_injected() {
+        let mut r_p = RandPhrase::new(4);
+        r_p.set_digit(true);
+        r_p.set_symbol(true);
+        r_p.join().unwrap();
+
+        let phrase = r_p.phrase();
+        assert_eq!(phrase.chars().filter(char::is_ascii_digit).count(), 1);
+        assert_eq!(phrase.chars().filter(char::is_ascii_punctuation).count(), 1);
+
+        // 4 words of 2048, 10 digits and 32 symbols after any of them
+        let bits = 44.0 + 40f64.log2() + 128f64.log2();
+        assert!((r_p.entropy().unwrap().bits() - bits).abs() < 1e-9);
+    }
+
+    #[test]
+    fn eff_file_and_seed() {
+        let path = env::temp_dir().join("rand_key_phrase_test.txt");
+        fs::write(&path, "11111\tabacus\n11112\tabdomen\n11113\tabdominal\n").unwrap();
+
+        let mut r_p = RandPhrase::new(5);
+        r_p.set_wordlist(Wordlist::from_file(&path).unwrap());
+        r_p.set_rng(RngSource::Seeded([3; 32]));
+        r_p.join().unwrap();
+        let first = r_p.phrase();
+        r_p.join().unwrap();
+
+        assert_eq!(first, r_p.phrase());
+        assert!(first.split(' ').all(|x| x.starts_with("ab")));
+        assert!(Wordlist::from_file(env::temp_dir().join("rand_key_no_such_file")).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn bip39_is_the_default() {
+        let list = Wordlist::bip39();
+        assert_eq!(list.len(), 2048);
+        assert!(list.words().iter().all(|x| (3..=8).contains(&x.len()) && x.chars().all(|x| x.is_ascii_lowercase())));
+        assert_eq!(list.words().iter().map(|x| &x[..4.min(x.len())]).collect::<HashSet<_>>().len(), 2048);
+
+        let r_p = RandPhrase::new(1);
+        assert_eq!(r_p.wordlist().words(), list.words());
+        assert_eq!(r_p.entropy().unwrap().bits(), 11.0);
+        assert_eq!(RandPhrase::new(12).entropy().unwrap().bits(), 132.0);
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "presets")]
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Presets {
+
+    use rand_key::{Presets, GenError, RngSource, ASCIIExcludeCtrl::*};
+    use std::{env, fs};
+
+    const TOML: &str = r#"
+        [db_password]
+        len = { min = 24, max = 32 }
+        exclude = ["l", "I", "0", "O"]
+        classes = [
+            { name = "Alphabetic", data = ["a", "b", "l", "I", "O"], range = { min = 1 } },
+            { name = "Punctuation", data = ["-", "_"], range = { min = 1, max = 3 } },
+            { name = "Digit", data = ["0", "1", "2"], range = { min = 2 } },
+        ]
+
+        [wifi]
+        unit = 4
+        rng = "Os"
+        classes = [{ name = "Digit", data = ["1", "2", "3"], cnt = 10 }]
+    "#;
+
+    fn field(err: GenError) -> (String, String) {
+        match err {
+            GenError::InvalidPolicy { preset, field, .. } => (preset, field),
+            err => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn toml_presets_are_instantiated_by_name() {
+        let presets = Presets::from_toml(TOML).unwrap();
+
+        let r_p = presets.get("db_password").unwrap();
+        r_p.join().unwrap();
+        assert!((24..=32).contains(&r_p.len()));
+        assert!(!r_p.key().contains(|x| "lI0O".contains(x)));
+        assert_eq!(r_p.data(Alphabetic).unwrap(), ["a", "b"]);
+
+        let r_p = presets.get("wifi").unwrap();
+        assert_eq!((r_p.unit(), r_p.rng(), r_p.get_cnt(Digit)), (4, RngSource::Os, 10));
+
+        assert!(matches!(presets.get("vpn"), Err(GenError::UnknownPreset(x)) if x == "vpn"));
+    }
+
+    #[test]
+    fn json_and_files_are_read() {
+        let json = r#"{ "api_token": { "classes": [{ "name": "Hex", "data": ["0", "f"], "cnt": 32 }] } }"#;
+        let dir = env::temp_dir();
+        let (toml_path, json_path) = (dir.join("rand_key_presets.toml"), dir.join("rand_key_presets.json"));
+        fs::write(&toml_path, TOML).unwrap();
+        fs::write(&json_path, json).unwrap();
+
+        assert_eq!(Presets::from_file(&toml_path).unwrap().names().collect::<Vec<_>>(), ["db_password", "wifi"]);
+        assert_eq!(Presets::from_file(&json_path).unwrap().get("api_token").unwrap().get_cnt("Hex"), 32);
+        assert_eq!(Presets::from_json(json).unwrap().names().count(), 1);
+
+        fs::remove_file(toml_path).unwrap();
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn errors_point_at_the_policy_and_field() {
+        let cases = [
+            ("[a]\nunit = 0\nclasses = []", "unit"),
+            ("[a]\nclasses = [{ name = \"D\", data = [\"1\"] }, { name = \"E\", data = [\"2\"], range = { min = 3, max = 1 } }]", "classes[1].range"),
+            ("[a]\nclasses = [{ name = \"D\", data = [\"1\"], cnnt = 1 }]", "classes[0].cnnt"),
+            ("[a]\nclasses = [{ name = \"D\", data = [\"\\u0007\"], cnt = 1 }]", "classes[0]"),
+            ("[a]\nclasses = []\nrng = \"Dice\"", "rng"),
+        ];
+
+        for (toml, path) in &cases {
+            assert_eq!(field(Presets::from_toml(toml).unwrap_err()), ("a".into(), path.to_string()));
+        }
+
+        let err = Presets::from_toml("[ok]\nclasses = []\n[bad]\nclasses = [{ name = \"D\", data = [], cnt = 1 }]").unwrap_err();
+        assert!(err.to_string().contains("`bad`") && err.to_string().contains("`D`"), "{}", err);
+
+        assert!(matches!(Presets::from_toml("[a"), Err(GenError::InvalidPresets(_))));
+        assert!(matches!(Presets::from_json("[]"), Err(GenError::InvalidPresets(_))));
+    }
+}
@@ -0,0 +1,107 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Range {
+
+    use std::{collections::HashMap, ops::Bound::*};
+    use rand_key::{RandKey, GenError, SetRandKeyOp::Check, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn compositions_are_uniform() {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.set_range(Alphabetic, 0..=2).unwrap();
+        r_p.set_range(Digit, ..3).unwrap();
+        r_p.set_len_range(1..=2).unwrap();
+
+        let mut seen = HashMap::new();
+        for _ in 0..5000 {
+            r_p.join().unwrap();
+            let key = r_p.key();
+            let ltr = key.chars().filter(char::is_ascii_alphabetic).count();
+            *seen.entry((ltr, key.len() - ltr)).or_insert(0) += 1;
+        }
+
+        // (0, 1), (1, 0), (0, 2), (1, 1), (2, 0)
+        assert_eq!(seen.len(), 5);
+        assert!(seen.values().all(|x| (850..1150).contains(x)), "{:?}", seen);
+    }
+
+    #[test]
+    fn bounded_compositions_are_uniform() {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.set_range(Alphabetic, ..=1).unwrap();
+        r_p.set_range(Digit, ..=2).unwrap();
+        r_p.set_len_range(..=3).unwrap();
+
+        let mut seen = HashMap::new();
+        for _ in 0..6000 {
+            r_p.join().unwrap();
+            let key = r_p.key();
+            let ltr = key.chars().filter(char::is_ascii_alphabetic).count();
+            *seen.entry((ltr, key.len() - ltr)).or_insert(0) += 1;
+        }
+
+        assert_eq!(seen.len(), 6);
+        assert!(seen.values().all(|x| (850..1150).contains(x)), "{:?}", seen);
+    }
+
+    #[test]
+    fn huge_lengths_fail_without_panics() {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.set_range(Alphabetic, ..).unwrap();
+        r_p.set_range(Digit, ..).unwrap();
+        r_p.set_len_range(0..=usize::MAX).unwrap();
+
+        assert!(matches!(r_p.join(), Err(GenError::TooLarge(_))));
+        assert!(matches!(r_p.generate(), Err(GenError::TooLarge(_))));
+    }
+
+    #[test]
+    fn check_follows_the_ranges() {
+        let mut r_p = RandKey::new("8", "0", "0").unwrap();
+        r_p.set_range(Digit, 2..=4).unwrap();
+
+        assert!(r_p.set_key("abcdefgh12", Check).is_ok());
+        assert!(r_p.set_key("abcdefgh1234", Check).is_ok());
+        assert!(r_p.set_key("abcdefgh1", Check).is_err());
+        assert!(r_p.set_key("abcdefg123", Check).is_err());
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        let (min, max) = (3, 2);
+        assert!(matches!(r_p.set_range(Digit, min..=max), Err(GenError::InvalidRange)));
+        assert!(matches!(r_p.set_range(Digit, ..0), Err(GenError::InvalidRange)));
+        assert!(matches!(r_p.set_len_range((Excluded(usize::MAX), Unbounded)), Err(GenError::InvalidRange)));
+        assert_eq!(r_p.get_range(Digit), None);
+
+        r_p.set_range(Digit, 2..).unwrap();
+        assert!(matches!(r_p.join(), Err(GenError::UnboundedRange)));
+
+        r_p.set_len_range(..2).unwrap();
+        assert!(matches!(r_p.join(), Err(GenError::InvalidRange)));
+
+        r_p.set_len_range(..=2).unwrap();
+        r_p.join().unwrap();
+        assert_eq!(r_p.key().len(), 2);
+    }
+
+    #[test]
+    fn empty_classes_miss_chars_either_way() {
+        let mut r_p = RandKey::new("4", "0", "2").unwrap();
+        r_p.clear(Digit).unwrap();
+        assert!(matches!(r_p.join(), Err(GenError::MissChar(x)) if x == "Digit"));
+
+        r_p.set_cnt(Digit, "0").unwrap();
+        r_p.set_range(Digit, 2..=3).unwrap();
+        assert!(matches!(r_p.join(), Err(GenError::MissChar(x)) if x == "Digit"));
+        assert!(matches!(r_p.entropy(), Err(GenError::MissChar(x)) if x == "Digit"));
+
+        // Nothing is missing if it may take none
+        r_p.set_range(Digit, 0..=3).unwrap();
+        r_p.join().unwrap();
+        assert_eq!(r_p.key().len(), 4);
+    }
+}
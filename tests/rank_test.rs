@@ -0,0 +1,78 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Rank {
+
+    use rand_key::{RandKey, Mask, CharClass, GenError, SetRandKeyOp, ASCIIExcludeCtrl::*};
+    use num_bigint::{BigUint, RandBigInt};
+    use num_traits::ToPrimitive;
+    use std::collections::HashSet;
+
+    fn small() -> RandKey {
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.replace_data(&["a", "b", "c", "-", "0", "1"]).unwrap();
+        r_p.add_class(CharClass::new("Space", &[" "], "0").unwrap()).unwrap();
+        r_p.set_range(Alphabetic, 1..=2).unwrap();
+        r_p.set_range(Digit, 0..).unwrap();
+        r_p.set_range("Space", ..=1).unwrap();
+        r_p.set_len_range(2..=4).unwrap();
+        r_p
+    }
+
+    #[test]
+    fn every_index_is_a_distinct_key_of_the_policy() {
+        let r_p = small();
+        let keyspace = r_p.keyspace().unwrap();
+        let mut keys = HashSet::new();
+
+        for i in 0..keyspace.to_u32().unwrap() {
+            let idx = BigUint::from(i);
+            let key = r_p.unrank(&idx).unwrap();
+
+            r_p.clone().set_key(&key, SetRandKeyOp::Check).unwrap();
+            assert_eq!(r_p.rank(&key).unwrap(), idx);
+            assert!(keys.insert(key.to_string()));
+        }
+
+        assert_eq!(BigUint::from(keys.len()), keyspace);
+        assert!(matches!(r_p.unrank(&keyspace), Err(GenError::InvalidIndex(x)) if x == keyspace.to_string()));
+    }
+
+    #[test]
+    fn large_keyspaces_round_trip() {
+        let mut r_p = RandKey::new("20", "8", "12").unwrap();
+        r_p.set_unambiguous(true);
+        let keyspace = r_p.keyspace().unwrap();
+
+        for _ in 0..50 {
+            let idx = rand::thread_rng().gen_biguint_below(&keyspace);
+            let key = r_p.unrank(&idx).unwrap();
+
+            assert_eq!(key.len(), 40);
+            assert_eq!(r_p.rank(&key).unwrap(), idx);
+        }
+
+        let mask = Mask::with_charsets("?1?u?d-?1", &[('1', "xyz🦀")]).unwrap();
+        assert_eq!(mask.keyspace(), BigUint::from(4u32 * 26 * 10 * 4));
+
+        for i in 0..4 * 26 * 10 * 4u32 {
+            let key = mask.unrank(&BigUint::from(i)).unwrap();
+            assert_eq!(mask.rank(&key).unwrap(), BigUint::from(i));
+        }
+    }
+
+    #[test]
+    fn keys_outside_the_policy_are_rejected() {
+        let r_p = small();
+
+        assert!(matches!(r_p.rank("ab?"), Err(GenError::InvalidChar(x)) if x == "?"));
+        assert!(matches!(r_p.rank("abc"), Err(GenError::InconsistentField(x)) if x == "Alphabetic"));
+        assert!(matches!(r_p.rank("a0101"), Err(GenError::InconsistentField(x)) if x == "length"));
+
+        let mask = Mask::new("id-?d?l").unwrap();
+        assert!(matches!(mask.rank("id_1a"), Err(GenError::InconsistentField(x)) if x == "-"));
+        assert!(matches!(mask.rank("id-1A"), Err(GenError::InconsistentField(x)) if x == "?l"));
+        assert!(matches!(mask.rank("id-1"), Err(GenError::InconsistentField(x)) if x == "length"));
+    }
+}
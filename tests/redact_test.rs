@@ -0,0 +1,32 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Redact {
+
+    use rand_key::{RandKey, RandKeyBuilder, RandPhrase, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn debug_shows_counts_only() {
+        let r_p = RandKey::new(10, 2, 3).unwrap();
+        r_p.join().unwrap();
+
+        let debug = format!("{:?}", r_p);
+        assert!(!debug.contains(&r_p.key()));
+        assert!(debug.contains("len: 15"));
+        assert!(debug.contains(r#"("Alphabetic", 10), ("Punctuation", 2), ("Digit", 3)"#));
+
+        let builder = format!("{:?}", RandKeyBuilder::new().cnt(Digit, 4));
+        assert!(builder.contains(r#"("Digit", 4)"#));
+    }
+
+    #[test]
+    fn phrase_is_redacted() {
+        let r_p = RandPhrase::new(4);
+        r_p.join().unwrap();
+
+        assert_eq!(r_p.to_string(), "<redacted>");
+        assert_eq!(format!("{:?}", r_p), r#"RandPhrase { phrase: <redacted>, word_cnt: 4 }"#);
+        assert_eq!(r_p.reveal().to_string(), r_p.phrase());
+    }
+}
@@ -0,0 +1,86 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Regex {
+
+    use rand_key::{RandRegex, GenError, RngSource};
+    use std::collections::HashMap;
+
+    #[test]
+    fn matches_keep_their_shape() {
+        let regex = RandRegex::new(r"^(?:[A-F]\d|x{2,3}?)-[^\w\s]{3}\.\w?(é|字)$").unwrap();
+
+        for _ in 0..300 {
+            let key = regex.generate().unwrap();
+            let (head, tail) = key.split_at(key.find('-').unwrap());
+            let tail: Vec<char> = tail.chars().collect();
+
+            assert!(matches!(head, "xx" | "xxx") || head.len() == 2 && head.starts_with(|x| ('A'..='F').contains(&x)));
+            assert!(tail[1..4].iter().all(|x| x.is_ascii_punctuation() && *x != '_'));
+            assert_eq!(tail[4], '.');
+            assert!("é字".contains(*tail.last().unwrap()));
+        }
+    }
+
+    #[test]
+    fn every_match_is_equally_likely() {
+        let mut regex = RandRegex::new("a|b[cd]|[ef]{0,1}").unwrap();
+        assert_eq!(regex.entropy().keyspace().to_string(), "6");
+
+        let mut hits = HashMap::new();
+        for _ in 0..6000 {
+            *hits.entry(regex.generate().unwrap().to_string()).or_insert(0) += 1;
+        }
+        assert_eq!(hits.len(), 6);
+        assert!(hits.values().all(|x| (850..1150).contains(x)), "{:?}", hits);
+
+        assert_eq!(RandRegex::new("[ab]{0,3}").unwrap().entropy().keyspace().to_string(), "15");
+
+        regex.set_rng(RngSource::Seeded([4; 32]));
+        assert_eq!(regex.generate().unwrap(), regex.generate().unwrap());
+    }
+
+    #[test]
+    fn a_string_matching_twice_counts_once() {
+        let keyspace = |x: &str| RandRegex::new(x).unwrap().entropy().keyspace().to_string();
+
+        // `a` matches `a?a?` in 2 ways
+        assert_eq!(keyspace("a?a?"), "3");
+        assert_eq!(keyspace("a|a"), "1");
+        assert_eq!(keyspace("(a|ab)(c|bc)"), "3");
+        assert_eq!(keyspace("[a-z]{2}|[a-c]{2}x?"), "685");
+        assert_eq!(keyspace("(a?){1000}"), "1001");
+
+        let regex = RandRegex::new("[ab]|b|c{0,1}b?").unwrap();
+        assert_eq!(regex.entropy().keyspace().to_string(), "5");
+
+        let mut hits = HashMap::new();
+        for _ in 0..5000 {
+            *hits.entry(regex.generate().unwrap().to_string()).or_insert(0) += 1;
+        }
+        assert_eq!(hits.len(), 5);
+        assert!(hits.values().all(|x| (850..1150).contains(x)), "{:?}", hits);
+    }
+
+    #[test]
+    fn unbounded_and_unsupported_constructs_are_rejected() {
+        let bad = [
+            "a*", "a+", "a{3,}", "a{,}", "a{3", "{2}", r"\bword", r"(a)\1", "(?=a)b", "(?i)a", "(a", "a)",
+            "[a", "[z-a]", "a{3,1}", r"[[:alpha:]]", "a^b", "a$b", "\u{7}", r"\n", r"[^ -~]",
+        ];
+
+        for regex in &bad {
+            assert!(matches!(RandRegex::new(regex), Err(GenError::InvalidRegex(_))), "{}", regex);
+        }
+
+        assert!(matches!(RandRegex::new("a*"), Err(GenError::InvalidRegex(x)) if x.contains("at 1")));
+        assert!(matches!(RandRegex::new("[ab]{0,3000000}"), Err(GenError::InvalidRegex(x)) if x.contains("steps")));
+        assert!(matches!(RandRegex::new("((){0,1000}){0,1000}"), Err(GenError::InvalidRegex(_))));
+        assert!(RandRegex::new("[ab]{0,20000}").unwrap().generate().unwrap().len() <= 20000);
+        assert!(matches!(RandRegex::new("[ab]{0,60000}"), Err(GenError::InvalidRegex(x)) if x.contains("too long")));
+        assert!(matches!(RandRegex::new("(a?){5000}"), Err(GenError::InvalidRegex(x)) if x.contains("too ambiguous")));
+        assert!(matches!(RandRegex::new(".{20000}"), Err(GenError::InvalidRegex(_))));
+        assert!(RandRegex::new("").unwrap().generate().unwrap().is_empty());
+    }
+}
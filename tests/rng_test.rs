@@ -0,0 +1,61 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Rng {
+
+    use rand_key::{RandKey, RngSource};
+    use rand::{rngs::OsRng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    fn counts(key: &str) -> [usize; 3] {
+        [key.chars().filter(char::is_ascii_alphabetic).count(),
+         key.chars().filter(char::is_ascii_punctuation).count(),
+         key.chars().filter(char::is_ascii_digit).count()]
+    }
+
+    #[test]
+    fn every_source_keeps_the_counts() {
+        let mut r_p = RandKey::new("30", "20", "10").unwrap();
+        assert_eq!(r_p.rng(), RngSource::Thread);
+
+        for rng in [RngSource::Thread, RngSource::Os, RngSource::ChaCha20, RngSource::Seeded([3; 32])] {
+            r_p.set_rng(rng);
+            r_p.join().unwrap();
+
+            assert_eq!(r_p.rng(), rng);
+            assert_eq!(counts(&r_p.key()), [30, 20, 10]);
+        }
+    }
+
+    #[test]
+    fn join_with_a_deterministic_rng() {
+        let r_p = RandKey::new("30", "20", "10").unwrap();
+
+        r_p.join_with(&mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+        let first = r_p.key();
+        r_p.join_with(&mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+        assert_eq!(r_p.key(), first);
+
+        r_p.join_with(&mut ChaCha20Rng::seed_from_u64(8)).unwrap();
+        assert_ne!(r_p.key(), first);
+
+        r_p.join_with(&mut OsRng).unwrap();
+        assert_eq!(counts(&r_p.key()), [30, 20, 10]);
+    }
+
+    #[test]
+    fn the_shuffle_draws_from_the_rng() {
+        let r_p = RandKey::new("1", "0", "1").unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+
+        // Both orders come out of one stream, so the layout isn't fixed
+        let firsts: Vec<bool> = (0..64).map(|_| {
+            r_p.join_with(&mut rng).unwrap();
+            r_p.key().starts_with(|x: char| x.is_ascii_digit())
+        })
+        .collect();
+
+        assert!(firsts.contains(&true) && firsts.contains(&false));
+    }
+}
@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Seeded {
+
+    use rand_key::{RandKey, RngSource, CharClass};
+    use rayon::ThreadPoolBuilder;
+
+    fn join_on(threads: usize, r_p: &RandKey) -> String {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let r_p = r_p.clone();
+        pool.install(move || {
+            r_p.join().unwrap();
+            r_p.key()
+        })
+    }
+
+    #[test]
+    fn same_key_on_any_thread_count() {
+        let mut r_p = RandKey::new("5000", "3000", "2000").unwrap();
+        r_p.set_unit("100").unwrap();
+        r_p.set_rng(RngSource::Seeded([42; 32]));
+
+        let expected = join_on(1, &r_p);
+
+        for threads in &[2, 4, 16, 64] {
+            assert_eq!(join_on(*threads, &r_p), expected);
+        }
+    }
+
+    #[test]
+    fn mixed_widths_on_any_thread_count() {
+        let mut r_p = RandKey::new("3000", "0", "2000").unwrap();
+        r_p.add_class(CharClass::from_chars("CJK", '\u{4E00}'..='\u{9FFF}', "1000").unwrap()).unwrap();
+        r_p.set_unit("64").unwrap();
+        r_p.set_rng(RngSource::Seeded([9; 32]));
+
+        let expected = join_on(1, &r_p);
+        assert_eq!(expected.chars().count(), 6000);
+
+        for threads in &[3, 8] {
+            assert_eq!(join_on(*threads, &r_p), expected);
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let r_p = RandKey::new("100", "10", "10").unwrap();
+
+        r_p.join_seeded([1; 32]).unwrap();
+        let first = r_p.key();
+        r_p.join_seeded([2; 32]).unwrap();
+
+        assert_ne!(first, r_p.key());
+    }
+}
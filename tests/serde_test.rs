@@ -0,0 +1,96 @@
+#![cfg(feature = "serde")]
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Serde {
+
+    use rand_key::{RandKey, RandKeyBuilder, CharClass, RngSource, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn policy_round_trips() {
+        let r_p = RandKeyBuilder::new()
+            .cnt(Alphabetic, 12)
+            .range(Digit, 2..=4)
+            .range(Punctuation, 1..)
+            .len_range(16..=20)
+            .class(CharClass::new("Space", &[" ", "字"], 1).unwrap())
+            .unambiguous(true)
+            .unit(7)
+            .rng(RngSource::Os)
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_string(&r_p).unwrap();
+        let back: RandKey = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        assert_eq!(back.all_data().len(), 4);
+        assert_eq!(back.get_range(Digit), Some((2, Some(4))));
+        assert_eq!(back.get_range(Punctuation), Some((1, None)));
+        assert_eq!(back.len_range(), Some((16, Some(20))));
+        assert_eq!(back.data("Space").unwrap(), [" ", "字"]);
+        assert_eq!(back.unit(), 7);
+        assert_eq!(back.rng(), RngSource::Os);
+        assert!(back.is_unambiguous());
+    }
+
+    #[test]
+    fn seed_is_never_serialized() {
+        let mut r_p = RandKey::new(10, 2, 3).unwrap();
+        r_p.set_rng(RngSource::Seeded([7; 32]));
+
+        let err = serde_json::to_string(&r_p).unwrap_err().to_string();
+        assert!(err.contains("seed"), "{}", err);
+        assert!(!err.contains("7, 7"));
+
+        let json = r#"{ "classes": [{ "name": "Digit", "data": ["1", "2"], "cnt": 4 }], "rng": { "Seeded": [7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7] } }"#;
+        assert!(serde_json::from_str::<RandKey>(json).is_err());
+    }
+
+    #[test]
+    fn key_is_never_serialized() {
+        let r_p = RandKey::new(10, 2, 3).unwrap();
+        r_p.join().unwrap();
+
+        let json = serde_json::to_string(&r_p).unwrap();
+        assert!(!json.contains("key"));
+
+        let back: RandKey = serde_json::from_str(&json).unwrap();
+        assert!(back.is_empty());
+        assert_eq!(back.get_cnt(Digit), 3);
+    }
+
+    #[test]
+    fn exclusions_and_defaults_apply() {
+        let json = r#"{
+            "classes": [
+                { "name": "Digit", "data": ["0", "1", "2", "3"], "cnt": 6 },
+                { "name": "Vowel", "data": ["a", "e", "o"], "cnt": 2 }
+            ],
+            "exclude": ["0", "o"]
+        }"#;
+        let r_p: RandKey = serde_json::from_str(json).unwrap();
+
+        assert_eq!(r_p.data("Digit").unwrap(), ["1", "2", "3"]);
+        assert_eq!(r_p.data("Vowel").unwrap(), ["a", "e"]);
+        assert_eq!(r_p.rng(), RngSource::Thread);
+        assert!(!serde_json::to_string(&r_p).unwrap().contains("exclude"));
+    }
+
+    #[test]
+    fn invalid_policy_is_rejected() {
+        let bad = [
+            r#"{ "classes": [{ "name": "Digit", "data": ["12"], "cnt": 1 }] }"#,
+            r#"{ "classes": [{ "name": "Digit", "data": [], "cnt": 1 }] }"#,
+            r#"{ "classes": [{ "name": "Digit", "data": ["1"], "range": { "min": 3, "max": 2 } }] }"#,
+            r#"{ "classes": [{ "name": "Digit", "data": ["1"] }, { "name": "Digit", "data": ["2"] }] }"#,
+            r#"{ "classes": [{ "name": "Digit", "data": ["1"], "cnt": 1 }], "unit": 0 }"#,
+            r#"{ "classes": [{ "name": "Digit", "data": ["1"], "cnt": 1 }], "key": "1" }"#,
+        ];
+
+        for json in &bad {
+            assert!(serde_json::from_str::<RandKey>(json).is_err(), "{}", json);
+        }
+    }
+}
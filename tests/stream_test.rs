@@ -0,0 +1,62 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Stream {
+
+    use rand_key::{RandKey, RngSource};
+    use std::io::{self, Write};
+
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> { Err(io::ErrorKind::BrokenPipe.into()) }
+        fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn chunks_keep_the_exact_counts() {
+        let r_p = RandKey::new("3000", "2000", "1000").unwrap();
+        r_p.set_unit("7").unwrap();
+
+        let mut out = Vec::new();
+        r_p.join_to_writer(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.chars().filter(char::is_ascii_alphabetic).count(), 3000);
+        assert_eq!(out.chars().filter(char::is_ascii_punctuation).count(), 2000);
+        assert_eq!(out.chars().filter(char::is_ascii_digit).count(), 1000);
+    }
+
+    #[test]
+    fn positions_are_uniform() {
+        let r_p = RandKey::new("3", "0", "1").unwrap();
+        let mut hits = [0usize; 4];
+
+        for _ in 0..4000 {
+            let mut out = Vec::new();
+            r_p.join_to_writer(&mut out).unwrap();
+            hits[out.iter().position(u8::is_ascii_digit).unwrap()] += 1;
+        }
+
+        assert!(hits.iter().all(|x| (800..1200).contains(x)), "{:?}", hits);
+    }
+
+    #[test]
+    fn seeded_streams_are_reproducible() {
+        let mut r_p = RandKey::new("500", "200", "100").unwrap();
+        r_p.set_rng(RngSource::Seeded([3; 32]));
+
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        r_p.join_to_writer(&mut a).unwrap();
+        r_p.join_to_writer(&mut b).unwrap();
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn write_errors_are_returned() {
+        let r_p = RandKey::new("10", "2", "3").unwrap();
+        assert!(r_p.join_to_writer(Broken).is_err());
+    }
+}
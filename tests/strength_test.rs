@@ -0,0 +1,89 @@
+#![cfg(feature = "bundled-dictionaries")]
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Strength {
+
+    use rand_key::{RandKey, Strength, Pattern, Dictionary, Dictionaries};
+
+    fn patterns(password: &str) -> Vec<(std::ops::Range<usize>, Pattern)> {
+        Strength::estimate(password).sequence().iter().map(|x| (x.range(), *x.pattern())).collect()
+    }
+
+    #[test]
+    fn common_patterns_are_weak() {
+        let word = |rank, l33t, reversed| Pattern::Dictionary { dictionary: Dictionary::Passwords, rank, l33t, reversed };
+
+        assert_eq!(patterns("password"), [(0..8, word(2, false, false))]);
+        assert_eq!(patterns("P@ssw0rd"), [(0..8, word(2, true, false))]);
+        assert_eq!(patterns("drowssap"), [(0..8, word(2, false, true))]);
+        assert_eq!(patterns("abcdef"), [(0..6, Pattern::Sequence { ascending: true })]);
+        assert_eq!(patterns("97531"), [(0..5, Pattern::Sequence { ascending: false })]);
+        assert_eq!(patterns("xyzxyzxyz"), [(0..9, Pattern::Repeat { times: 3 })]);
+        assert_eq!(patterns("!QAZ2wsx"), [(0..4, Pattern::Spatial { turns: 1, shifted: 4 }), (4..8, Pattern::Spatial { turns: 1, shifted: 0 })]);
+        assert_eq!(patterns("25.12.1990"), [(0..10, Pattern::Date { separator: true })]);
+        assert_eq!(patterns("19901225"), [(0..8, Pattern::Date { separator: false })]);
+
+        for password in ["password", "P@ssw0rd", "abcdef", "xyzxyzxyz", "qwertyuiop", "aaaaaaaaaaaa", "19901225"] {
+            assert!(Strength::estimate(password).score() <= 1, "{}", password);
+        }
+    }
+
+    #[test]
+    fn user_inputs_weaken_a_password() {
+        let alone = Strength::estimate("Zeb_Quorra");
+        let known = Strength::estimate_with("Zeb_Quorra", &["quorra", "zeb"]);
+
+        assert!(known.guesses() < alone.guesses());
+        assert!(matches!(known.sequence()[2].pattern(), Pattern::Dictionary { dictionary: Dictionary::UserInputs, rank: 1, .. }));
+        assert_eq!(known.sequence().iter().map(|x| x.range()).collect::<Vec<_>>(), [0..3, 3..4, 4..10]);
+    }
+
+    #[test]
+    fn random_keys_are_strong() {
+        let r_p = RandKey::new(10, 4, 4).unwrap();
+
+        for _ in 0..20 {
+            r_p.join().unwrap();
+            let strength = Strength::estimate(&r_p.key());
+
+            assert_eq!(strength.score(), 4);
+            assert!(strength.guesses_log10() > 20.0, "{}", strength.guesses_log10());
+        }
+
+        let empty = Strength::estimate("");
+        assert_eq!((empty.guesses(), empty.score()), (1.0, 0));
+        assert!(empty.sequence().is_empty());
+    }
+
+    #[test]
+    fn dictionaries_are_pluggable() {
+        let path = std::env::temp_dir().join("rand_key_strength_test.txt");
+        std::fs::write(&path, "zebraquorra 3120\n\n123456 2900\n").unwrap();
+
+        let mut dicts = Dictionaries::new();
+        dicts.set_from_file(Dictionary::Names, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((dicts.size(Dictionary::Names), dicts.size(Dictionary::Passwords)), (2, 0));
+        assert!(matches!(dicts.estimate("ZebraQuorra").sequence()[0].pattern(), Pattern::Dictionary { dictionary: Dictionary::Names, rank: 1, .. }));
+        assert!(dicts.estimate("ZebraQuorra").guesses() < Strength::estimate("ZebraQuorra").guesses());
+
+        dicts.set(Dictionary::Names, &["quorra"]);
+        assert_eq!(dicts.size(Dictionary::Names), 1);
+        assert_eq!(Dictionaries::bundled().estimate("password").guesses(), Strength::estimate("password").guesses());
+        assert!(Dictionaries::new().set_from_file(Dictionary::English, "/no/such/file").is_err());
+    }
+
+    #[test]
+    fn long_passwords_stay_cheap() {
+        let long = "aZ3$".repeat(50_000);
+        let strength = Strength::estimate(&long);
+
+        // Only the first 100 characters are matched, the rest is cut off
+        assert_eq!(strength.guesses(), Strength::estimate(&long[..100]).guesses());
+        assert_eq!(strength.sequence(), Strength::estimate(&long[..100]).sequence());
+        assert_eq!(strength.sequence().last().unwrap().range().end, 100);
+    }
+}
@@ -0,0 +1,37 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Sync {
+
+    use rand_key::{RandKey, RandPhrase};
+    use std::{sync::Arc, thread};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn generators_are_send_and_sync() {
+        assert_send_sync::<RandKey>();
+        assert_send_sync::<RandPhrase>();
+    }
+
+    #[test]
+    fn one_generator_joins_from_many_threads() {
+        let r_p = Arc::new(RandKey::new("10", "2", "3").unwrap());
+
+        let handles: Vec<_> =
+            (0..8).map(|_| {
+                      let r_p = Arc::clone(&r_p);
+                      thread::spawn(move || {
+                          (0..100).for_each(|_| {
+                              r_p.join().unwrap();
+                              assert_eq!(r_p.generate().unwrap().len(), 15);
+                          })
+                      })
+                  })
+                  .collect();
+
+        handles.into_iter().for_each(|x| x.join().unwrap());
+        assert_eq!(r_p.len(), 15);
+    }
+}
@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Unicode {
+
+    use rand_key::{RandKey, CharClass, ToRandKey, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn to_randkey_keeps_the_counts() {
+        let r_p = "Пароль🦀️2020".to_randkey().unwrap();
+
+        assert_eq!(r_p.get_cnt(Alphabetic), 6);
+        assert_eq!(r_p.get_cnt(Punctuation), 1);
+        assert_eq!(r_p.get_cnt(Digit), 4);
+
+        r_p.join().unwrap();
+        assert_eq!(r_p.len(), 11);
+    }
+
+    #[test]
+    fn multi_char_items_are_never_torn_apart() {
+        let thumbs = ["👍🏻", "👍🏽", "👍🏿"];
+
+        let mut r_p = RandKey::new("0", "0", "0").unwrap();
+        r_p.add_class(CharClass::new("Thumb", &thumbs, "50").unwrap()).unwrap();
+        r_p.add_class(CharClass::from_chars("Greek", 'α'..='ω', "50").unwrap()).unwrap();
+        r_p.join().unwrap();
+
+        let key = r_p.key();
+        let mut rest = key.as_str();
+        let mut thumb_cnt = 0;
+
+        while let Some(c) = rest.chars().next() {
+            match thumbs.iter().find(|x| rest.starts_with(*x)) {
+                Some(x) => {
+                    thumb_cnt += 1;
+                    rest = &rest[x.len()..];
+                }
+                None => {
+                    assert!(('α'..='ω').contains(&c));
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        assert_eq!(thumb_cnt, 50);
+        assert_eq!(r_p.len(), 100);
+    }
+
+    #[test]
+    fn items_must_be_single_characters() {
+        assert!(CharClass::new("Word", &["ab"], "1").is_err());
+        assert!(CharClass::new("Bell", &["\u{7}"], "1").is_err());
+        assert!(RandKey::default().add_item(&["字", "ж"]).is_ok());
+    }
+}
@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Validate {
+
+    use rand_key::{RandKey, RandKeyBuilder, Violation, ASCIIExcludeCtrl::*};
+
+    #[test]
+    fn generated_keys_are_valid() {
+        let r_p = RandKeyBuilder::new().range(Alphabetic, 4..=8).cnt(Digit, 3).len_range(8..).unambiguous(true).build().unwrap();
+
+        for _ in 0..50 {
+            r_p.join().unwrap();
+            assert_eq!(r_p.validate(&r_p.key()), []);
+        }
+
+        let r_p = RandKey::new(10, 2, 3).unwrap();
+        r_p.join().unwrap();
+        assert_eq!(r_p.validate(&r_p.key()), []);
+    }
+
+    #[test]
+    fn every_violation_is_listed() {
+        let r_p = RandKeyBuilder::new().cnt(Alphabetic, 4).cnt(Digit, 2).unambiguous(true).build().unwrap();
+
+        assert_eq!(r_p.validate("abc0d3\u{7}字"), [
+            Violation::Length { len: 8, min: 6, max: Some(6) },
+            Violation::Count { class: "Digit".into(), cnt: 1, min: 2, max: Some(2) },
+            Violation::Forbidden("0".into()),
+            Violation::NotAllowed("\u{7}".into()),
+            Violation::NotAllowed("字".into()),
+        ]);
+
+        let validator = r_p.validator().forbid(&["x", "y"]).max_repeat(1);
+        assert_eq!(validator.validate("xxab44xyxxx"), [
+            Violation::Length { len: 11, min: 6, max: Some(6) },
+            Violation::Count { class: "Alphabetic".into(), cnt: 9, min: 4, max: Some(4) },
+            Violation::Forbidden("x".into()),
+            Violation::Forbidden("y".into()),
+            Violation::Repeat { item: "x".into(), run: 3, max: 1 },
+            Violation::Repeat { item: "4".into(), run: 2, max: 1 },
+        ]);
+    }
+
+    #[test]
+    fn violations_explain_themselves() {
+        let messages: Vec<String> = RandKey::new(2, 0, 0).unwrap().validator().max_repeat(2).validate("....").iter().map(ToString::to_string).collect();
+
+        assert_eq!(messages, [
+            "The length is 4, but it has to be exactly 2",
+            "It has 0 of `Alphabetic`, but requires exactly 2",
+            "It has 4 of `Punctuation`, but requires exactly 0",
+            "`.` is repeated 4 times in a row, at most 2 are allowed",
+        ]);
+    }
+}
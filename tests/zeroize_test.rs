@@ -0,0 +1,50 @@
+#![allow(non_snake_case)]
+
+
+#[cfg(test)]
+mod Secret {
+
+    use rand_key::{RandKey, RandPhrase, RngSource, Zeroizing, ASCIIExcludeCtrl::*};
+    use zeroize::Zeroize;
+
+    #[test]
+    fn secret_matches_the_key() {
+        let mut r_p = RandKey::new(20, 4, 6).unwrap();
+
+        for unit in &[1, 7, 1000] {
+            r_p.set_unit(*unit).unwrap();
+            r_p.join().unwrap();
+            assert_eq!(r_p.key_secret().as_str(), r_p.key());
+        }
+
+        // Characters of different widths take the concatenating path
+        r_p.add_item(&["字", "🦀"]).unwrap();
+        r_p.join_seeded([3; 32]).unwrap();
+        assert_eq!(r_p.key_secret().as_str(), r_p.key());
+        assert_eq!(r_p.len(), 30);
+    }
+
+    #[test]
+    fn generated_key_is_wiped() {
+        let r_p = RandKey::new(0, 0, 8).unwrap();
+        let mut key: Zeroizing<String> = r_p.generate().unwrap();
+
+        assert_eq!(key.len(), 8);
+        assert!(key.chars().all(|x| r_p.data(Digit).unwrap().contains(&x.to_string().as_str())));
+
+        key.zeroize();
+        assert!(key.is_empty());
+    }
+
+    #[test]
+    fn phrase_secret_matches_the_phrase() {
+        let mut r_p = RandPhrase::new(6);
+        r_p.set_digit(true);
+        r_p.set_symbol(true);
+        r_p.set_rng(RngSource::Seeded([9; 32]));
+        r_p.join().unwrap();
+
+        assert_eq!(r_p.phrase_secret().as_str(), r_p.phrase());
+        assert!(r_p.phrase().contains(|x: char| x.is_ascii_digit()));
+    }
+}